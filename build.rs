@@ -89,6 +89,14 @@ fn build_command() -> Command {
                 .long("allocated-bytes")
                 .help("Use raw allocated size"),
         )
+        .arg(
+            Arg::new("escape")
+                .action(ArgAction::SetTrue)
+                .short('b')
+                .long("escape")
+                .overrides_with_all(["literal", "quote-name", "quoting-style"])
+                .help("Print C style escapes for nongraphic characters, like --quoting-style=escape"),
+        )
         .arg(
             Arg::new("changed")
                 .action(ArgAction::SetTrue)
@@ -100,9 +108,22 @@ fn build_command() -> Command {
             Arg::new("vertical")
                 .action(ArgAction::SetTrue)
                 .short('C')
-                .overrides_with_all(["across", "long", "single-column"])
+                .overrides_with_all(["across", "commas", "long", "output", "single-column", "zero"])
                 .help("List entries top-to-bottom in a grid"),
         )
+        .arg(
+            Arg::new("collate")
+                .action(ArgAction::Set)
+                .long("collate")
+                .value_parser([
+                    PossibleValue::new("bytes").help("Compare bytes, thus uppercase is ordered first like the C locale"),
+                    PossibleValue::new("case-insensitive").help("Compare names ignoring case"),
+                    PossibleValue::new("locale").help("Compare letters and digits first ignoring case, like UTF-8 locales"),
+                    PossibleValue::new("natural").help("Compare runs of digits by their numeric value"),
+                ])
+                .value_name("WORD")
+                .help("Set how file names are compared when sorting. Defaults to locale for UTF-8 locales and to bytes for the C locale set by LC_COLLATE"),
+        )
         .arg(
             Arg::new("color")
                 .action(ArgAction::Set)
@@ -117,6 +138,18 @@ fn build_command() -> Command {
                 .num_args(0..=1)
                 .help("Flag to control when to use color for output"),
         )
+        .arg(
+            Arg::new("columns")
+                .action(ArgAction::Set)
+                .long("columns")
+                .value_parser(value_parser!(String))
+                .value_name("LIST")
+                .help(
+                    "List the comma separated columns in LIST in long format [possible columns: \
+                     inode, blocks, mode, nlink, user, group, size, time, atime, btime, ctime, \
+                     mtime, name]",
+                ),
+        )
         .arg(
             Arg::new("directory")
                 .action(ArgAction::SetTrue)
@@ -124,6 +157,27 @@ fn build_command() -> Command {
                 .long("directory")
                 .help("Do not list directory contents, instead list the directory itself"),
         )
+        .arg(
+            Arg::new("dired")
+                .action(ArgAction::SetTrue)
+                .short('D')
+                .long("dired")
+                .help("Like -l but print byte offsets of file names for Emacs' dired mode, without colors or icons"),
+        )
+        .arg(
+            Arg::new("all-unsorted")
+                .action(ArgAction::SetTrue)
+                .short('f')
+                .overrides_with_all([
+                    "extension-sort",
+                    "size-sort",
+                    "sort",
+                    "timestamp-sort",
+                    "unsorted",
+                    "version-sort",
+                ])
+                .help("Like -aU but also do not use color for output"),
+        )
         .arg(
             Arg::new("classify")
                 .action(ArgAction::SetTrue)
@@ -132,6 +186,37 @@ fn build_command() -> Command {
                 .overrides_with("slash")
                 .help("Append filetype indicator (either */=@|) to entry file names"),
         )
+        .arg(
+            Arg::new("format-string")
+                .action(ArgAction::Set)
+                .long("format-string")
+                .value_parser(value_parser!(String))
+                .value_name("FORMAT")
+                .overrides_with_all([
+                    "across",
+                    "commas",
+                    "long",
+                    "output",
+                    "single-column",
+                    "vertical",
+                    "zero",
+                ])
+                .help("Print each entry on its own line using FORMAT e.g. '{mode} {size:h} {name}'"),
+        )
+        .arg(
+            Arg::new("full-time")
+                .action(ArgAction::SetTrue)
+                .long("full-time")
+                .overrides_with_all([
+                    "across",
+                    "commas",
+                    "output",
+                    "single-column",
+                    "vertical",
+                    "zero",
+                ])
+                .help("Like -l --time-style=full-iso"),
+        )
         .arg(
             Arg::new("list-owner")
                 .action(ArgAction::SetFalse)
@@ -144,6 +229,58 @@ fn build_command() -> Command {
                 .long("gitignore")
                 .help("Ignore files set in '.gitignore' files"),
         )
+        .arg(
+            Arg::new("grid-columns")
+                .action(ArgAction::Set)
+                .long("grid-columns")
+                .value_parser(value_parser!(String))
+                .value_name("LIST")
+                .help(
+                    "List the comma separated columns in LIST in each cell of grids and each \
+                     line of -1, -m and --zero [possible columns: inode, blocks, mode, nlink, \
+                     user, group, size, time, atime, btime, ctime, mtime, name]",
+                ),
+        )
+        .arg(
+            Arg::new("group-by")
+                .action(ArgAction::Set)
+                .long("group-by")
+                .value_parser([
+                    PossibleValue::new("type").help("Group directories, files, symlinks then other file types"),
+                    PossibleValue::new("extension").help("Group entries by extension"),
+                    PossibleValue::new("owner").help("Group entries by owner"),
+                ])
+                .value_name("WORD")
+                .overrides_with("group-directories-first")
+                .help("Group entries before sorting them within each group"),
+        )
+        .arg(
+            Arg::new("group-directories-first")
+                .action(ArgAction::SetTrue)
+                .long("group-directories-first")
+                .overrides_with("group-by")
+                .help("List directories before other entries"),
+        )
+        .arg(
+            Arg::new("group-headings")
+                .action(ArgAction::SetTrue)
+                .long("group-headings")
+                .help("Print a heading before each group set by '--group-by' or '--group-directories-first'"),
+        )
+        .arg(
+            Arg::new("header")
+                .action(ArgAction::SetTrue)
+                .long("header")
+                .help("Print a header line labelling each column"),
+        )
+        .arg(
+            Arg::new("hide-control-chars")
+                .action(ArgAction::SetTrue)
+                .short('q')
+                .long("hide-control-chars")
+                .overrides_with("show-control-chars")
+                .help("Print ? instead of control characters. This is the default when printing to a terminal"),
+        )
         .arg(
             Arg::new("human-readable")
                 .action(ArgAction::SetTrue)
@@ -182,6 +319,20 @@ fn build_command() -> Command {
                 .value_name("PATTERN")
                 .help("Ignore entries matching glob pattern")
         )
+        .arg(
+            Arg::new("hyperlink")
+                .action(ArgAction::Set)
+                .long("hyperlink")
+                .value_parser([
+                    PossibleValue::new("always").help("Always link file names"),
+                    PossibleValue::new("auto").help("Link file names only if stdout is a tty"),
+                    PossibleValue::new("never").help("Never link file names"),
+                ])
+                .value_name("WHEN")
+                .default_missing_value("always")
+                .num_args(0..=1)
+                .help("Flag to control when to link file names to their paths with OSC 8 hyperlinks")
+        )
         .arg(
             Arg::new("icons")
                 .action(ArgAction::Set)
@@ -209,6 +360,12 @@ fn build_command() -> Command {
                 .long("ignore-file")
                 .help("Ignore files set in '.ignore' files"),
         )
+        .arg(
+            Arg::new("ignore-leading-dots")
+                .action(ArgAction::SetTrue)
+                .long("ignore-leading-dots")
+                .help("Ignore leading dots of file names when sorting"),
+        )
         .arg(
             Arg::new("kibibytes")
                 .action(ArgAction::SetTrue)
@@ -220,9 +377,23 @@ fn build_command() -> Command {
             Arg::new("long")
                 .action(ArgAction::SetTrue)
                 .short('l')
-                .overrides_with_all(["across", "single-column", "vertical"])
+                .overrides_with_all([
+                    "across",
+                    "commas",
+                    "output",
+                    "single-column",
+                    "vertical",
+                    "zero",
+                ])
                 .help("List entries along with their metadata in long format"),
         )
+        .arg(
+            Arg::new("commas")
+                .action(ArgAction::SetTrue)
+                .short('m')
+                .overrides_with_all(["across", "long", "output", "single-column", "vertical", "zero"])
+                .help("List entries separated by commas, filling the width of the terminal"),
+        )
         .arg(
             Arg::new("dereference")
                 .action(ArgAction::SetTrue)
@@ -251,6 +422,20 @@ fn build_command() -> Command {
                 .value_name("WORD")
                 .help("Set the mode format to be used in long format"),
         )
+        .arg(
+            Arg::new("no-sort-args")
+                .action(ArgAction::SetTrue)
+                .long("no-sort-args")
+                .help("List paths passed on the command line in the order given instead of sorting them"),
+        )
+        .arg(
+            Arg::new("literal")
+                .action(ArgAction::SetTrue)
+                .short('N')
+                .long("literal")
+                .overrides_with_all(["escape", "quote-name", "quoting-style"])
+                .help("Print file names without quoting, like --quoting-style=literal"),
+        )
         .arg(
             Arg::new("numeric-uid-gid")
                 .action(ArgAction::SetTrue)
@@ -264,6 +449,23 @@ fn build_command() -> Command {
                 .short('o')
                 .help("Like -l but do not list the group column"),
         )
+        .arg(
+            Arg::new("output")
+                .action(ArgAction::Set)
+                .long("output")
+                .value_parser([
+                    PossibleValue::new("csv")
+                        .help("Print the long format columns as comma separated values"),
+                    PossibleValue::new("json")
+                        .help("Print a JSON array of entries for each directory listed"),
+                    PossibleValue::new("ndjson").help("Print a JSON object for each entry per line"),
+                    PossibleValue::new("tsv")
+                        .help("Print the long format columns as tab separated values"),
+                ])
+                .value_name("WORD")
+                .overrides_with_all(["across", "commas", "long", "single-column", "vertical", "zero"])
+                .help("Print entries in a machine readable format"),
+        )
         .arg(
             Arg::new("slash")
                 .action(ArgAction::SetTrue)
@@ -271,6 +473,32 @@ fn build_command() -> Command {
                 .overrides_with("classify")
                 .help("Append filetype indicator / to directory entry file names"),
         )
+        .arg(
+            Arg::new("quote-name")
+                .action(ArgAction::SetTrue)
+                .short('Q')
+                .long("quote-name")
+                .overrides_with_all(["escape", "literal", "quoting-style"])
+                .help("Enclose file names in double quotes, like --quoting-style=c"),
+        )
+        .arg(
+            Arg::new("quoting-style")
+                .action(ArgAction::Set)
+                .long("quoting-style")
+                .value_parser([
+                    PossibleValue::new("literal").help("Print file names as is. This is the default when not printing to a terminal"),
+                    PossibleValue::new("locale").help("Quote file names with the quotation marks of the locale"),
+                    PossibleValue::new("shell").help("Quote file names for the shell if needed"),
+                    PossibleValue::new("shell-always").help("Always quote file names for the shell"),
+                    PossibleValue::new("shell-escape").help("Like shell, but escape control characters e.g. $'\\n'. This is the default when printing to a terminal"),
+                    PossibleValue::new("shell-escape-always").help("Like shell-always, but escape control characters"),
+                    PossibleValue::new("c").help("Quote file names like C strings"),
+                    PossibleValue::new("escape").help("Like c, but without quotation marks"),
+                ])
+                .value_name("WORD")
+                .overrides_with_all(["escape", "literal", "quote-name"])
+                .help("Set how file names are quoted. Defaults to the QUOTING_STYLE environment variable if set"),
+        )
         .arg(
             Arg::new("reverse")
                 .action(ArgAction::SetTrue)
@@ -285,6 +513,13 @@ fn build_command() -> Command {
                 .long("recursive")
                 .help("Recurse into directories"),
         )
+        .arg(
+            Arg::new("show-control-chars")
+                .action(ArgAction::SetTrue)
+                .long("show-control-chars")
+                .overrides_with("hide-control-chars")
+                .help("Print control characters as is. This is the default when not printing to a terminal"),
+        )
         .arg(
             Arg::new("list-allocated-size")
                 .action(ArgAction::SetTrue)
@@ -296,7 +531,14 @@ fn build_command() -> Command {
             Arg::new("size-sort")
                 .action(ArgAction::SetTrue)
                 .short('S')
-                .overrides_with("timestamp-sort")
+                .overrides_with_all([
+                    "all-unsorted",
+                    "extension-sort",
+                    "sort",
+                    "timestamp-sort",
+                    "unsorted",
+                    "version-sort",
+                ])
                 .help("Sort entries by largest size first"),
         )
         .arg(
@@ -306,13 +548,50 @@ fn build_command() -> Command {
                 .overrides_with_all(["human-readable", "iec"])
                 .help("Format size using factors of 1000 like 1.0K 200M 3.0G etc"),
         )
+        .arg(
+            Arg::new("sort")
+                .action(ArgAction::Set)
+                .long("sort")
+                .value_parser(value_parser!(String))
+                .value_name("KEYS")
+                .overrides_with_all([
+                    "all-unsorted",
+                    "extension-sort",
+                    "size-sort",
+                    "timestamp-sort",
+                    "unsorted",
+                    "version-sort",
+                ])
+                .help(
+                    "Sort entries by the comma separated KEYS e.g. 'type,ext,-size', where a \
+                     leading '-' reverses a key and ties are sorted by file name [possible keys: \
+                     none, name, size, time, extension, ext, version, width, inode, nlink, \
+                     owner, group, btime, type]",
+                ),
+        )
         .arg(
             Arg::new("timestamp-sort")
                 .action(ArgAction::SetTrue)
                 .short('t')
-                .overrides_with("size-sort")
+                .overrides_with_all([
+                    "all-unsorted",
+                    "extension-sort",
+                    "size-sort",
+                    "sort",
+                    "unsorted",
+                    "version-sort",
+                ])
                 .help("Sort entries by most recent timestamp first"),
         )
+        .arg(
+            Arg::new("tabsize")
+                .action(ArgAction::Set)
+                .short('T')
+                .long("tabsize")
+                .value_parser(value_parser!(usize))
+                .value_name("COLS")
+                .help("Pad grids with tabs of COLS columns where possible. Defaults to 0, which pads with spaces only"),
+        )
         .arg(
             Arg::new("time")
                 .action(ArgAction::Set)
@@ -331,11 +610,50 @@ fn build_command() -> Command {
                     PossibleValue::new("mtime").help("Alias to 'modified'"),
                 ])
                 .value_name("WORD")
+                .value_delimiter(',')
                 .overrides_with_all(["accessed", "changed"])
                 .help(
-                    "Set timestamp to use for sorting by timestamp or/and listing in long format",
+                    "Set timestamp to use for sorting by timestamp or/and listing in long format. \
+                     A comma separated list lists each timestamp in long format and sorts by the first",
+                ),
+        )
+        .arg(
+            Arg::new("time-style")
+                .action(ArgAction::Set)
+                .long("time-style")
+                .value_parser(value_parser!(String))
+                .value_name("STYLE")
+                .help(
+                    "Set the timestamp format to be used in long format, either full-iso, \
+                     long-iso, iso, locale, relative, relative-compact or +FORMAT where FORMAT \
+                     is a strftime format. \
+                     +FORMAT1<newline>FORMAT2 uses FORMAT2 for timestamps older than 6 months",
                 ),
         )
+        .arg(
+            Arg::new("tree")
+                .action(ArgAction::SetTrue)
+                .long("tree")
+                .help("Recurse into directories and list entries as a tree"),
+        )
+        .arg(
+            Arg::new("tz")
+                .action(ArgAction::Set)
+                .long("tz")
+                .value_parser(value_parser!(String))
+                .value_name("ZONE")
+                .help(
+                    "Set the time zone timestamps are shown in, either UTC, a time zone name \
+                     e.g. Europe/Berlin or an offset e.g. +05:30. Defaults to the TZ \
+                     environment variable or the local time zone",
+                ),
+        )
+        .arg(
+            Arg::new("tz-offset")
+                .action(ArgAction::SetTrue)
+                .long("tz-offset")
+                .help("Append the UTC offset to timestamps"),
+        )
         .arg(
             Arg::new("accessed")
                 .action(ArgAction::SetTrue)
@@ -343,24 +661,93 @@ fn build_command() -> Command {
                 .overrides_with_all(["changed", "time"])
                 .help("Use timestamp of when entry was last accessed"),
         )
+        .arg(
+            Arg::new("unsorted")
+                .action(ArgAction::SetTrue)
+                .short('U')
+                .overrides_with_all([
+                    "all-unsorted",
+                    "extension-sort",
+                    "size-sort",
+                    "sort",
+                    "timestamp-sort",
+                    "version-sort",
+                ])
+                .help("Do not sort, list entries in directory order"),
+        )
         .arg(
             Arg::new("version")
                 .action(ArgAction::Version)
                 .long("version")
                 .help("Print version information"),
         )
+        .arg(
+            Arg::new("version-sort")
+                .action(ArgAction::SetTrue)
+                .short('v')
+                .overrides_with_all([
+                    "all-unsorted",
+                    "extension-sort",
+                    "size-sort",
+                    "sort",
+                    "timestamp-sort",
+                    "unsorted",
+                ])
+                .help("Sort entries by natural ordering of version numbers in file names"),
+        )
+        .arg(
+            Arg::new("walk")
+                .action(ArgAction::Set)
+                .long("walk")
+                .value_parser([
+                    PossibleValue::new("depth-first").help("List each subdirectory before the next subdirectory of its parent"),
+                    PossibleValue::new("breadth-first").help("List all subdirectories at a depth before those at the next depth"),
+                ])
+                .value_name("WORD")
+                .help("Set the order directories are visited in when recursing. Subdirectories are visited in the sorting order"),
+        )
+        .arg(
+            Arg::new("width")
+                .action(ArgAction::Set)
+                .short('w')
+                .long("width")
+                .value_parser(value_parser!(usize))
+                .value_name("COLS")
+                .help("Set the line width, where 0 means unlimited. Defaults to the terminal width, then the COLUMNS environment variable, then 80"),
+        )
         .arg(
             Arg::new("across")
                 .action(ArgAction::SetTrue)
                 .short('x')
-                .overrides_with_all(["long", "single-column", "vertical"])
+                .overrides_with_all(["commas", "long", "output", "single-column", "vertical", "zero"])
                 .help("List entries left-to-right in a grid"),
         )
+        .arg(
+            Arg::new("extension-sort")
+                .action(ArgAction::SetTrue)
+                .short('X')
+                .overrides_with_all([
+                    "all-unsorted",
+                    "size-sort",
+                    "sort",
+                    "timestamp-sort",
+                    "unsorted",
+                    "version-sort",
+                ])
+                .help("Sort entries alphabetically by extension"),
+        )
+        .arg(
+            Arg::new("zero")
+                .action(ArgAction::SetTrue)
+                .long("zero")
+                .overrides_with_all(["across", "commas", "long", "output", "single-column", "vertical"])
+                .help("End each entry and directory header with NUL instead of newline, without colors or icons"),
+        )
         .arg(
             Arg::new("single-column")
                 .action(ArgAction::SetTrue)
                 .short('1')
-                .overrides_with_all(["across", "long", "vertical"])
+                .overrides_with_all(["across", "commas", "long", "output", "vertical", "zero"])
                 .help("List one entry per line"),
         )
 }
@@ -19,16 +19,36 @@ _nls() {
 
     case "${cmd}" in
         nls)
-            opts="-a -A -c -C -d -F -g -h -H -i -I -k -l -L -n -o -p -r -R -s -S -t -u -x -1 --all --almost-all --allocated-bytes --color --directory --classify --gitignore --human-readable --dereference-command-line --help --inode --ignore-glob --icons --iec --ignore-file --kibibytes --dereference --max-depth --mode --numeric-uid-gid --reverse --recursive --size --si --time --version [FILE]..."
+            opts="-a -A -b -c -C -d -D -f -F -g -q -h -H -i -I -k -l -m -L -N -n -o -p -Q -r -R -s -S -t -T -u -U -v -w -x -X -1 --all --almost-all --allocated-bytes --escape --collate --color --columns --directory --dired --classify --format-string --full-time --gitignore --grid-columns --group-by --group-directories-first --group-headings --header --hide-control-chars --human-readable --dereference-command-line --help --inode --ignore-glob --hyperlink --icons --iec --ignore-file --ignore-leading-dots --kibibytes --dereference --max-depth --mode --no-sort-args --literal --numeric-uid-gid --output --quote-name --quoting-style --reverse --recursive --show-control-chars --size --si --sort --tabsize --time --time-style --tree --tz --tz-offset --version --walk --width --zero [FILE]..."
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 1 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
             fi
             case "${prev}" in
+                --collate)
+                    COMPREPLY=($(compgen -W "bytes case-insensitive locale natural" -- "${cur}"))
+                    return 0
+                    ;;
                 --color)
                     COMPREPLY=($(compgen -W "always auto never" -- "${cur}"))
                     return 0
                     ;;
+                --columns)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --format-string)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --grid-columns)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --group-by)
+                    COMPREPLY=($(compgen -W "type extension owner" -- "${cur}"))
+                    return 0
+                    ;;
                 --ignore-glob)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
@@ -37,6 +57,10 @@ _nls() {
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
+                --hyperlink)
+                    COMPREPLY=($(compgen -W "always auto never" -- "${cur}"))
+                    return 0
+                    ;;
                 --icons)
                     COMPREPLY=($(compgen -W "always auto never" -- "${cur}"))
                     return 0
@@ -49,10 +73,50 @@ _nls() {
                     COMPREPLY=($(compgen -W "native pwsh rwx" -- "${cur}"))
                     return 0
                     ;;
+                --output)
+                    COMPREPLY=($(compgen -W "csv json ndjson tsv" -- "${cur}"))
+                    return 0
+                    ;;
+                --quoting-style)
+                    COMPREPLY=($(compgen -W "literal locale shell shell-always shell-escape shell-escape-always c escape" -- "${cur}"))
+                    return 0
+                    ;;
+                --sort)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --tabsize)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                -T)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
                 --time)
                     COMPREPLY=($(compgen -W "accessed changed created modified atime ctime btime mtime" -- "${cur}"))
                     return 0
                     ;;
+                --time-style)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --tz)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --walk)
+                    COMPREPLY=($(compgen -W "depth-first breadth-first" -- "${cur}"))
+                    return 0
+                    ;;
+                --width)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                -w)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
                 *)
                     COMPREPLY=()
                     ;;
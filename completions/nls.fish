@@ -1,37 +1,71 @@
+complete -c nls -l collate -d 'Set how file names are compared when sorting. Defaults to locale for UTF-8 locales and to bytes for the C locale set by LC_COLLATE' -r -f -a "{bytes	'Compare bytes, thus uppercase is ordered first like the C locale',case-insensitive	'Compare names ignoring case',locale	'Compare letters and digits first ignoring case, like UTF-8 locales',natural	'Compare runs of digits by their numeric value'}"
 complete -c nls -l color -d 'Flag to control when to use color for output' -r -f -a "{always	'Always use color for output',auto	'Color for output only if stdout is a tty',never	'Never use color for output'}"
+complete -c nls -l columns -d 'List the comma separated columns in LIST in long format [possible columns: inode, blocks, mode, nlink, user, group, size, time, atime, btime, ctime, mtime, name]' -r
+complete -c nls -l format-string -d 'Print each entry on its own line using FORMAT e.g. \'{mode} {size:h} {name}\'' -r
+complete -c nls -l grid-columns -d 'List the comma separated columns in LIST in each cell of grids and each line of -1, -m and --zero [possible columns: inode, blocks, mode, nlink, user, group, size, time, atime, btime, ctime, mtime, name]' -r
+complete -c nls -l group-by -d 'Group entries before sorting them within each group' -r -f -a "{type	'Group directories, files, symlinks then other file types',extension	'Group entries by extension',owner	'Group entries by owner'}"
 complete -c nls -s I -l ignore-glob -d 'Ignore entries matching glob pattern' -r
+complete -c nls -l hyperlink -d 'Flag to control when to link file names to their paths with OSC 8 hyperlinks' -r -f -a "{always	'Always link file names',auto	'Link file names only if stdout is a tty',never	'Never link file names'}"
 complete -c nls -l icons -d 'Flag to control when to display icons' -r -f -a "{always	'Always display icons',auto	'Display icons only if stdout is a tty',never	'Never display icons'}"
 complete -c nls -l max-depth -d 'Set the max depth to recurse into' -r
 complete -c nls -l mode -d 'Set the mode format to be used in long format' -r -f -a "{native	'Use the platform default mode format',pwsh	'Use powershell mode format e.g. \'darhsl\'. This is the default on windows',rwx	'Use symbolic format e.g. \'drwxrwxrwx\'. This is the default on unix like platforms'}"
-complete -c nls -l time -d 'Set timestamp to use for sorting by timestamp or/and listing in long format' -r -f -a "{accessed	'Use timestamp of when entry was last accessed (-u)',changed	'Use timestamp of when entry status was last changed (-c)',created	'Use timestamp of when entry was created',modified	'Use timestamp of when entry was last modified',atime	'Alias to \'accessed\'',ctime	'Alias to \'changed\'',btime	'Alias to \'created\'',mtime	'Alias to \'modified\''}"
+complete -c nls -l output -d 'Print entries in a machine readable format' -r -f -a "{csv	'Print the long format columns as comma separated values',json	'Print a JSON array of entries for each directory listed',ndjson	'Print a JSON object for each entry per line',tsv	'Print the long format columns as tab separated values'}"
+complete -c nls -l quoting-style -d 'Set how file names are quoted. Defaults to the QUOTING_STYLE environment variable if set' -r -f -a "{literal	'Print file names as is. This is the default when not printing to a terminal',locale	'Quote file names with the quotation marks of the locale',shell	'Quote file names for the shell if needed',shell-always	'Always quote file names for the shell',shell-escape	'Like shell, but escape control characters e.g. $\'\\n\'. This is the default when printing to a terminal',shell-escape-always	'Like shell-always, but escape control characters',c	'Quote file names like C strings',escape	'Like c, but without quotation marks'}"
+complete -c nls -l sort -d 'Sort entries by the comma separated KEYS e.g. \'type,ext,-size\', where a leading \'-\' reverses a key and ties are sorted by file name [possible keys: none, name, size, time, extension, ext, version, width, inode, nlink, owner, group, btime, type]' -r
+complete -c nls -s T -l tabsize -d 'Pad grids with tabs of COLS columns where possible. Defaults to 0, which pads with spaces only' -r
+complete -c nls -l time -d 'Set timestamp to use for sorting by timestamp or/and listing in long format. A comma separated list lists each timestamp in long format and sorts by the first' -r -f -a "{accessed	'Use timestamp of when entry was last accessed (-u)',changed	'Use timestamp of when entry status was last changed (-c)',created	'Use timestamp of when entry was created',modified	'Use timestamp of when entry was last modified',atime	'Alias to \'accessed\'',ctime	'Alias to \'changed\'',btime	'Alias to \'created\'',mtime	'Alias to \'modified\''}"
+complete -c nls -l time-style -d 'Set the timestamp format to be used in long format, either full-iso, long-iso, iso, locale, relative, relative-compact or +FORMAT where FORMAT is a strftime format. +FORMAT1<newline>FORMAT2 uses FORMAT2 for timestamps older than 6 months' -r
+complete -c nls -l tz -d 'Set the time zone timestamps are shown in, either UTC, a time zone name e.g. Europe/Berlin or an offset e.g. +05:30. Defaults to the TZ environment variable or the local time zone' -r
+complete -c nls -l walk -d 'Set the order directories are visited in when recursing. Subdirectories are visited in the sorting order' -r -f -a "{depth-first	'List each subdirectory before the next subdirectory of its parent',breadth-first	'List all subdirectories at a depth before those at the next depth'}"
+complete -c nls -s w -l width -d 'Set the line width, where 0 means unlimited. Defaults to the terminal width, then the COLUMNS environment variable, then 80' -r
 complete -c nls -s a -l all -d 'Include hidden entries in listing'
 complete -c nls -s A -l almost-all -d 'Like -a, --all but do not list implied . and .. entries'
 complete -c nls -l allocated-bytes -d 'Use raw allocated size'
+complete -c nls -s b -l escape -d 'Print C style escapes for nongraphic characters, like --quoting-style=escape'
 complete -c nls -s c -d 'Use timestamp of when entry status was last changed'
 complete -c nls -s C -d 'List entries top-to-bottom in a grid'
 complete -c nls -s d -l directory -d 'Do not list directory contents, instead list the directory itself'
+complete -c nls -s D -l dired -d 'Like -l but print byte offsets of file names for Emacs\' dired mode, without colors or icons'
+complete -c nls -s f -d 'Like -aU but also do not use color for output'
 complete -c nls -s F -l classify -d 'Append filetype indicator (either */=@|) to entry file names'
+complete -c nls -l full-time -d 'Like -l --time-style=full-iso'
 complete -c nls -s g -d 'Like -l but do not list the owner column'
 complete -c nls -l gitignore -d 'Ignore files set in \'.gitignore\' files'
+complete -c nls -l group-directories-first -d 'List directories before other entries'
+complete -c nls -l group-headings -d 'Print a heading before each group set by \'--group-by\' or \'--group-directories-first\''
+complete -c nls -l header -d 'Print a header line labelling each column'
+complete -c nls -s q -l hide-control-chars -d 'Print ? instead of control characters. This is the default when printing to a terminal'
 complete -c nls -s h -l human-readable -d 'Format size using factors of 1024 like 1.0K 200M 3.0G etc'
 complete -c nls -s H -l dereference-command-line -d 'Use target information of symlinks passed as arguments on command line'
 complete -c nls -l help -d 'Print help information'
 complete -c nls -s i -l inode -d 'List entries along with their file inode number'
 complete -c nls -l iec -d 'Format size using factors of 1024 like 1.0Ki 200Mi 3.0Gi etc'
 complete -c nls -l ignore-file -d 'Ignore files set in \'.ignore\' files'
+complete -c nls -l ignore-leading-dots -d 'Ignore leading dots of file names when sorting'
 complete -c nls -s k -l kibibytes -d 'Use 1024 byte blocks for allocated size'
 complete -c nls -s l -d 'List entries along with their metadata in long format'
+complete -c nls -s m -d 'List entries separated by commas, filling the width of the terminal'
 complete -c nls -s L -l dereference -d 'Use target information when listing symlink entries'
+complete -c nls -l no-sort-args -d 'List paths passed on the command line in the order given instead of sorting them'
+complete -c nls -s N -l literal -d 'Print file names without quoting, like --quoting-style=literal'
 complete -c nls -s n -l numeric-uid-gid -d 'Like -l but list the owner and group names as their respective uid and gid'
 complete -c nls -s o -d 'Like -l but do not list the group column'
 complete -c nls -s p -d 'Append filetype indicator / to directory entry file names'
+complete -c nls -s Q -l quote-name -d 'Enclose file names in double quotes, like --quoting-style=c'
 complete -c nls -s r -l reverse -d 'Reverse sorting order'
 complete -c nls -s R -l recursive -d 'Recurse into directories'
+complete -c nls -l show-control-chars -d 'Print control characters as is. This is the default when not printing to a terminal'
 complete -c nls -s s -l size -d 'List entries along with their allocated size'
 complete -c nls -s S -d 'Sort entries by largest size first'
 complete -c nls -l si -d 'Format size using factors of 1000 like 1.0K 200M 3.0G etc'
 complete -c nls -s t -d 'Sort entries by most recent timestamp first'
+complete -c nls -l tree -d 'Recurse into directories and list entries as a tree'
+complete -c nls -l tz-offset -d 'Append the UTC offset to timestamps'
 complete -c nls -s u -d 'Use timestamp of when entry was last accessed'
+complete -c nls -s U -d 'Do not sort, list entries in directory order'
 complete -c nls -l version -d 'Print version information'
+complete -c nls -s v -d 'Sort entries by natural ordering of version numbers in file names'
 complete -c nls -s x -d 'List entries left-to-right in a grid'
+complete -c nls -s X -d 'Sort entries alphabetically by extension'
+complete -c nls -l zero -d 'End each entry and directory header with NUL instead of newline, without colors or icons'
 complete -c nls -s 1 -d 'List one entry per line'
@@ -3,10 +3,16 @@ use std::io::{self, IsTerminal};
 use std::path::PathBuf;
 use std::process;
 
+use chrono::FixedOffset;
 use compact_str::{format_compact, CompactString};
 
+use crate::locale::{is_posix_collation_locale, is_posix_locale, TimeLocale};
 use crate::ls_colors::LsColors;
+use crate::output::{
+    is_valid_strftime_format, to_chrono_strftime_format, FormatString, LongColumn,
+};
 use crate::theme::{IconTheme, ThemeConfig};
+use crate::utils::terminal_width;
 
 const HELP: &str = include_str!(concat!(env!("OUT_DIR"), "/help-page.txt"));
 const VERSION: &str = concat!("nls-ls ", env!("CARGO_PKG_VERSION"));
@@ -14,19 +20,29 @@ const VERSION: &str = concat!("nls-ls ", env!("CARGO_PKG_VERSION"));
 #[derive(Debug)]
 pub struct Config {
     pub is_atty: bool,
+    pub collation: Collation,
     pub color: bool,
     pub dereference: bool,
     pub dereference_cmdline_symlink: bool,
     pub dereference_cmdline_symlink_dir: bool,
+    pub dired: bool,
     pub git_ignore: bool,
+    pub group_by: Option<GroupBy>,
+    pub group_headings: bool,
+    pub header: bool,
+    pub hide_control_chars: bool,
+    pub hyperlink: bool,
     pub ignore_file: bool,
     pub ignore_glob_vec: Vec<CompactString>,
     pub ignore_hidden: bool,
+    pub ignore_leading_dots: bool,
     pub indicator_style: IndicatorStyle,
     pub ls_colors: LsColors,
     pub mode_format: ModeFormat,
+    pub format_string: FormatString,
     pub numeric_uid_gid: bool,
     pub output_format: OutputFormat,
+    pub quoting_style: QuotingStyle,
     pub recursive: bool,
     pub max_depth: Option<usize>,
     pub reverse: bool,
@@ -37,9 +53,21 @@ pub struct Config {
     pub allocated_size_blocks: AllocatedSizeBlocks,
     pub list_owner: bool,
     pub list_group: bool,
+    pub columns: Option<Vec<LongColumn>>,
+    pub grid_columns: Option<Vec<LongColumn>>,
     pub size_format: SizeFormat,
-    pub sorting_order: SortingOrder,
+    pub sort_keys: Vec<SortKey>,
+    pub sort_args: bool,
+    pub tabsize: usize,
     pub timestamp_used: TimestampUsed,
+    pub extra_timestamps_used: Vec<TimestampUsed>,
+    pub time_style: TimeStyle,
+    pub time_locale: Option<&'static TimeLocale>,
+    pub time_zone: TimeZoneSetting,
+    pub show_tz_offset: bool,
+    pub tree: bool,
+    pub walk_order: WalkOrder,
+    pub width: usize,
     pub icons: IconTheme,
     pub theme: ThemeConfig,
 }
@@ -53,25 +81,94 @@ impl Config {
             config.color = true;
             config.icons = IconTheme::with_default_icons();
             config.output_format = OutputFormat::Vertical;
+            config.quoting_style = QuotingStyle::ShellEscape;
+            config.hide_control_chars = true;
+        }
+        if let Some(terminal_width) = terminal_width() {
+            config.width = terminal_width;
+        } else if let Some(columns_os) = std::env::var_os("COLUMNS") {
+            let columns = columns_os.to_string_lossy();
+            match parse_width(&columns) {
+                Some(width) => config.width = width,
+                None => eprintln!(
+                    "nls: ignoring invalid value of 'COLUMNS' environment variable: '{}'",
+                    columns
+                ),
+            }
+        }
+        if let Some(quoting_style_os) = std::env::var_os("QUOTING_STYLE") {
+            let quoting_style = quoting_style_os.to_string_lossy();
+            match QuotingStyle::from_word(&quoting_style) {
+                Some(quoting_style) => config.quoting_style = quoting_style,
+                None => eprintln!(
+                    "nls: ignoring invalid value of 'QUOTING_STYLE' environment variable: '{}'",
+                    quoting_style
+                ),
+            }
+        }
+        config.collation = Collation::from_env();
+        if let Some(time_style_os) = std::env::var_os("TIME_STYLE") {
+            let time_style = time_style_os.to_string_lossy();
+            match TimeStyle::parse(&time_style) {
+                Some(time_style) => config.time_style = time_style,
+                None => {
+                    eprintln!(
+                        "nls: '{}' is an invalid value for 'TIME_STYLE' environment variable",
+                        time_style
+                    );
+                    process::exit(1);
+                }
+            }
         }
         if let Err(err) = config.parse_args(std::env::args_os().skip(1), &mut path_args_vec) {
             eprintln!("nls: {}", err);
             process::exit(1);
         }
 
+        config.time_locale = TimeLocale::from_env();
+
+        // Named time zones are resolved by chrono's `Local`, which reads `TZ`
+        if let TimeZoneSetting::Named(name) = &config.time_zone {
+            std::env::set_var("TZ", name.as_str());
+        }
+
+        // Offsets of file names would be shifted by escape sequences and icons
+        if config.dired {
+            if config.output_format.is_long() {
+                config.color = false;
+                config.hyperlink = false;
+                config.icons = IconTheme::default();
+            } else {
+                config.dired = false;
+            }
+        }
+
+        if config.output_format == OutputFormat::Zero {
+            config.color = false;
+            config.hyperlink = false;
+            config.icons = IconTheme::default();
+            config.tree = false;
+        }
+
         if config.color {
             config.ls_colors = LsColors::with_colors();
             config.theme = ThemeConfig::with_default_colors();
         }
 
+        if config.output_format.is_machine_readable() {
+            config.color = false;
+            config.hyperlink = false;
+            config.icons = IconTheme::default();
+            config.allocated_size_blocks = AllocatedSizeBlocks::Raw;
+            config.tree = false;
+        }
+
         if !config.dereference_cmdline_symlink_dir {
             config.dereference_cmdline_symlink_dir = !(!config.list_dir
                 || config.indicator_style.is_classify()
                 || config.output_format.is_long())
         }
 
-        path_args_vec.sort();
-
         (config, path_args_vec)
     }
 
@@ -102,12 +199,35 @@ impl Config {
                         self.allocated_size_blocks = AllocatedSizeBlocks::Raw;
                     }
                 }
+                Short('b') | Long("escape") => {
+                    self.quoting_style = QuotingStyle::Escape;
+                }
                 Short('c') => {
                     self.timestamp_used = TimestampUsed::Changed;
+                    self.extra_timestamps_used.clear();
                 }
                 Short('C') => {
                     self.output_format = OutputFormat::Vertical;
                 }
+                Long("collate") => {
+                    let word = parser.value()?;
+
+                    if word == "bytes" {
+                        self.collation = Collation::Bytes;
+                    } else if word == "case-insensitive" {
+                        self.collation = Collation::CaseInsensitive;
+                    } else if word == "locale" {
+                        self.collation = Collation::Locale;
+                    } else if word == "natural" {
+                        self.collation = Collation::Natural;
+                    } else {
+                        return Err(anyhow!(
+                            "'{}' is an invalid argument for '--collate'\n\
+                             possible arguments are ['bytes', 'case-insensitive', 'locale', 'natural']",
+                            word.to_string_lossy()
+                        ));
+                    }
+                }
                 Long("color") => match parser.optional_value() {
                     Some(when) => {
                         if when == "always" {
@@ -126,19 +246,79 @@ impl Config {
                     }
                     None => self.color = true,
                 },
+                Long("columns") => {
+                    let value_os = parser.value()?;
+                    self.columns = Some(LongColumn::parse_list(
+                        &value_os.to_string_lossy(),
+                        "--columns",
+                    )?);
+                    self.set_long_output_format();
+                }
                 Short('d') | Long("directory") => {
                     self.list_dir = false;
                 }
+                Short('D') | Long("dired") => {
+                    self.dired = true;
+                    self.set_long_output_format();
+                }
+                Short('f') => {
+                    self.list_current_and_parent_dirs = true;
+                    self.ignore_hidden = false;
+                    self.set_sorting_order(SortingOrder::None);
+                    self.color = false;
+                }
                 Short('F') | Long("classify") => {
                     self.indicator_style = IndicatorStyle::Classify;
                 }
+                Long("format-string") => {
+                    let value_os = parser.value()?;
+                    self.format_string = FormatString::parse(&value_os.to_string_lossy())?;
+                    self.output_format = OutputFormat::FormatString;
+                }
                 Short('g') => {
                     self.list_owner = false;
-                    self.output_format = OutputFormat::Long;
+                    self.set_long_output_format();
+                }
+                Long("full-time") => {
+                    self.set_long_output_format();
+                    self.time_style = TimeStyle::FullIso;
                 }
                 Long("gitignore") => {
                     self.git_ignore = true;
                 }
+                Long("grid-columns") => {
+                    let value_os = parser.value()?;
+                    self.grid_columns = Some(LongColumn::parse_list(
+                        &value_os.to_string_lossy(),
+                        "--grid-columns",
+                    )?);
+                }
+                Long("group-by") => {
+                    let word = parser.value()?;
+
+                    if word == "type" {
+                        self.group_by = Some(GroupBy::FileType);
+                    } else if word == "extension" {
+                        self.group_by = Some(GroupBy::Extension);
+                    } else if word == "owner" {
+                        self.group_by = Some(GroupBy::Owner);
+                    } else {
+                        return Err(anyhow!(
+                            "'{}' is an invalid argument for '--group-by'\n\
+                             possible arguments are ['type', 'extension', 'owner']",
+                            word.to_string_lossy()
+                        ));
+                    }
+                }
+                Long("group-directories-first") => {
+                    self.group_by = Some(GroupBy::Directories);
+                }
+                Long("group-headings") => {
+                    self.group_headings = true;
+                }
+                Long("header") => {
+                    self.header = true;
+                }
                 Short('h') | Long("human-readable") => {
                     self.size_format = SizeFormat::HumanReadable;
                     self.allocated_size_blocks = AllocatedSizeBlocks::Raw;
@@ -160,6 +340,24 @@ impl Config {
                     self.ignore_glob_vec
                         .push(format_compact!("!{}", value_os.to_string_lossy()));
                 }
+                Long("hyperlink") => match parser.optional_value() {
+                    Some(when) => {
+                        if when == "always" {
+                            self.hyperlink = true;
+                        } else if when == "auto" {
+                            self.hyperlink = self.is_atty;
+                        } else if when == "never" {
+                            self.hyperlink = false;
+                        } else {
+                            return Err(anyhow!(
+                                "'{}' is an invalid argument for '--hyperlink'\n\
+                                 possible arguments are ['always', 'auto', 'never']",
+                                when.to_string_lossy()
+                            ));
+                        }
+                    }
+                    None => self.hyperlink = true,
+                },
                 Long("icons") => match parser.optional_value() {
                     Some(when) => {
                         if when == "always" {
@@ -186,6 +384,9 @@ impl Config {
                     self.size_format = SizeFormat::Iec;
                     self.allocated_size_blocks = AllocatedSizeBlocks::Raw;
                 }
+                Long("ignore-leading-dots") => {
+                    self.ignore_leading_dots = true;
+                }
                 Long("ignore-file") => {
                     self.ignore_file = true;
                 }
@@ -195,7 +396,10 @@ impl Config {
                     }
                 }
                 Short('l') => {
-                    self.output_format = OutputFormat::Long;
+                    self.set_long_output_format();
+                }
+                Short('m') => {
+                    self.output_format = OutputFormat::Commas;
                 }
                 Short('L') | Long("dereference") => {
                     self.dereference = true;
@@ -223,65 +427,198 @@ impl Config {
                         ));
                     }
                 }
+                Short('N') | Long("literal") => {
+                    self.quoting_style = QuotingStyle::Literal;
+                }
                 Short('n') | Long("numeric-uid-gid") => {
                     self.numeric_uid_gid = true;
-                    self.output_format = OutputFormat::Long;
+                    self.set_long_output_format();
+                }
+                Long("no-sort-args") => {
+                    self.sort_args = false;
                 }
                 Short('o') => {
                     self.list_group = false;
-                    self.output_format = OutputFormat::Long;
+                    self.set_long_output_format();
+                }
+                Long("output") => {
+                    let word = parser.value()?;
+
+                    if word == "json" {
+                        self.output_format = OutputFormat::Json;
+                    } else if word == "ndjson" {
+                        self.output_format = OutputFormat::Ndjson;
+                    } else if word == "csv" {
+                        self.output_format = OutputFormat::Csv;
+                    } else if word == "tsv" {
+                        self.output_format = OutputFormat::Tsv;
+                    } else {
+                        return Err(anyhow!(
+                            "'{}' is an invalid argument for '--output'\n\
+                             possible arguments are ['csv', 'json', 'ndjson', 'tsv']",
+                            word.to_string_lossy()
+                        ));
+                    }
                 }
                 Short('p') => {
                     self.indicator_style = IndicatorStyle::Slash;
                 }
+                Short('q') | Long("hide-control-chars") => {
+                    self.hide_control_chars = true;
+                }
+                Short('Q') | Long("quote-name") => {
+                    self.quoting_style = QuotingStyle::C;
+                }
+                Long("quoting-style") => {
+                    let word = parser.value()?;
+                    let word = word.to_string_lossy();
+
+                    match QuotingStyle::from_word(&word) {
+                        Some(quoting_style) => self.quoting_style = quoting_style,
+                        None => {
+                            return Err(anyhow!(
+                                "'{}' is an invalid argument for '--quoting-style'\n\
+                                 possible arguments are ['literal', 'locale', 'shell', 'shell-always', 'shell-escape', 'shell-escape-always', 'c', 'escape']",
+                                word
+                            ));
+                        }
+                    }
+                }
                 Short('r') | Long("reverse") => {
                     self.reverse = true;
                 }
                 Short('R') | Long("recursive") => {
                     self.recursive = true;
                 }
+                Long("show-control-chars") => {
+                    self.hide_control_chars = false;
+                }
                 Short('s') | Long("size") => {
                     self.list_allocated_size = true;
                 }
                 Short('S') => {
-                    self.sorting_order = SortingOrder::Size;
+                    self.set_sorting_order(SortingOrder::Size);
                 }
                 Long("si") => {
                     self.size_format = SizeFormat::Si;
                     self.allocated_size_blocks = AllocatedSizeBlocks::Raw;
                 }
                 Short('t') => {
-                    self.sorting_order = SortingOrder::Timestamp;
+                    self.set_sorting_order(SortingOrder::Timestamp);
+                }
+                Long("sort") => {
+                    let words = parser.value()?;
+                    self.sort_keys = SortKey::parse_list(&words.to_string_lossy())?;
+                }
+                Short('U') => {
+                    self.set_sorting_order(SortingOrder::None);
+                }
+                Short('v') => {
+                    self.set_sorting_order(SortingOrder::Version);
+                }
+                Short('X') => {
+                    self.set_sorting_order(SortingOrder::Extension);
+                }
+                Short('T') | Long("tabsize") => {
+                    let val: usize = parser.value()?.parse()?;
+                    self.tabsize = val;
                 }
                 Long("time") => {
+                    let words = parser.value()?;
+                    let words = words.to_string_lossy();
+                    let mut timestamps_used: Vec<TimestampUsed> = Vec::with_capacity(4);
+
+                    for word in words.split(',') {
+                        match TimestampUsed::from_word(word) {
+                            Some(timestamp_used) => timestamps_used.push(timestamp_used),
+                            None => {
+                                return Err(anyhow!(
+                                    "'{}' is an invalid argument for '--time'\n\
+                                     possible arguments are ['accessed', 'changed', 'created', 'modified', 'atime', 'ctime', 'btime', 'mtime']",
+                                    word
+                                ));
+                            }
+                        }
+                    }
+
+                    self.timestamp_used = timestamps_used.remove(0);
+                    self.extra_timestamps_used = timestamps_used;
+                }
+                Long("time-style") => {
                     let word = parser.value()?;
+                    let word = word.to_string_lossy();
 
-                    if word == "accessed" || word == "atime" {
-                        self.timestamp_used = TimestampUsed::Accessed;
-                    } else if word == "changed" || word == "ctime" {
-                        self.timestamp_used = TimestampUsed::Changed;
-                    } else if word == "created" || word == "btime" {
-                        self.timestamp_used = TimestampUsed::Created;
-                    } else if word == "modified" || word == "mtime" {
-                        self.timestamp_used = TimestampUsed::Modified;
-                    } else {
-                        return Err(anyhow!(
-                            "'{}' is an invalid argument for '--time'\n\
-                             possible arguments are ['accessed', 'changed', 'created', 'modified', 'atime', 'ctime', 'btime', 'mtime']",
-                            word.to_string_lossy()
-                        ));
+                    match TimeStyle::parse(&word) {
+                        Some(time_style) => self.time_style = time_style,
+                        None => {
+                            return Err(anyhow!(
+                                "'{}' is an invalid argument for '--time-style'\n\
+                                 possible arguments are ['full-iso', 'long-iso', 'iso', 'locale', 'relative', 'relative-compact', '+FORMAT']",
+                                word
+                            ));
+                        }
+                    }
+                }
+                Long("tz") => {
+                    let value_os = parser.value()?;
+                    let value = value_os.to_string_lossy();
+
+                    match TimeZoneSetting::parse(&value) {
+                        Some(time_zone) => self.time_zone = time_zone,
+                        None => {
+                            return Err(anyhow!(
+                                "'{}' is an invalid argument for '--tz'\n\
+                                 possible arguments are ['UTC', a time zone name e.g. 'Europe/Berlin', an offset e.g. '+05:30']",
+                                value
+                            ));
+                        }
                     }
                 }
+                Long("tz-offset") => {
+                    self.show_tz_offset = true;
+                }
+                Long("tree") => {
+                    self.tree = true;
+                    self.recursive = true;
+                }
                 Short('u') => {
                     self.timestamp_used = TimestampUsed::Accessed;
+                    self.extra_timestamps_used.clear();
                 }
                 Long("version") => {
                     println!("{}", VERSION);
                     process::exit(0);
                 }
+                Long("walk") => {
+                    let word = parser.value()?;
+
+                    if word == "depth-first" {
+                        self.walk_order = WalkOrder::DepthFirst;
+                    } else if word == "breadth-first" {
+                        self.walk_order = WalkOrder::BreadthFirst;
+                    } else {
+                        return Err(anyhow!(
+                            "'{}' is an invalid argument for '--walk'\n\
+                             possible arguments are ['depth-first', 'breadth-first']",
+                            word.to_string_lossy()
+                        ));
+                    }
+                }
+                Short('w') | Long("width") => {
+                    let value_os = parser.value()?;
+                    let value = value_os.to_string_lossy();
+                    self.width = parse_width(&value).ok_or_else(|| {
+                        anyhow!("'{}' is an invalid argument for '--width'", value)
+                    })?;
+                }
                 Short('x') => {
                     self.output_format = OutputFormat::Across;
                 }
+                Long("zero") => {
+                    self.output_format = OutputFormat::Zero;
+                    self.quoting_style = QuotingStyle::Literal;
+                    self.hide_control_chars = false;
+                }
                 Short('1') => {
                     self.output_format = OutputFormat::SingleColumn;
                 }
@@ -293,25 +630,76 @@ impl Config {
 
         Ok(())
     }
+
+    /// Returns the columns to list in long format, either set by '--columns'
+    /// or selected by '-i', '-s', '-g' and '-o'.
+    pub fn long_columns(&self) -> Vec<LongColumn> {
+        match &self.columns {
+            Some(columns) => columns.clone(),
+            None => LongColumn::default_columns(self),
+        }
+    }
+
+    /// Returns the columns to list next to each other in a grid cell or line of '-1',
+    /// either set by '--grid-columns' or selected by '-i' and '-s'.
+    pub fn grid_columns(&self) -> Vec<LongColumn> {
+        if let Some(grid_columns) = &self.grid_columns {
+            return grid_columns.clone();
+        }
+
+        let mut columns: Vec<LongColumn> = Vec::with_capacity(3);
+        if self.list_inode {
+            columns.push(LongColumn::Inode);
+        }
+        if self.list_allocated_size {
+            columns.push(LongColumn::Blocks);
+        }
+        columns.push(LongColumn::Name);
+
+        columns
+    }
+
+    /// Sorts entries by `sorting_order` only, as set by '-S', '-t', '-U', '-v' and '-X'.
+    fn set_sorting_order(&mut self, sorting_order: SortingOrder) {
+        self.sort_keys = vec![SortKey::new(sorting_order)];
+    }
+
+    /// Sets the output format to long format for flags that imply '-l',
+    /// unless a machine readable output format that lists the same columns is set.
+    fn set_long_output_format(&mut self) {
+        if !self.output_format.is_machine_readable() {
+            self.output_format = OutputFormat::Long;
+        }
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
             is_atty: false,
+            collation: Collation::default(),
             color: false,
             dereference: false,
             dereference_cmdline_symlink: false,
             dereference_cmdline_symlink_dir: false,
+            dired: false,
             git_ignore: false,
+            group_by: None,
+            group_headings: false,
+            header: false,
+            hide_control_chars: false,
+            hyperlink: false,
             ignore_file: false,
             ignore_glob_vec: Vec::default(),
             ignore_hidden: true,
+            ignore_leading_dots: false,
             indicator_style: IndicatorStyle::default(),
             ls_colors: LsColors::default(),
             mode_format: ModeFormat::default(),
+            format_string: FormatString::default(),
             numeric_uid_gid: false,
             output_format: OutputFormat::default(),
+            quoting_style: QuotingStyle::default(),
             recursive: false,
             max_depth: None,
             reverse: false,
@@ -321,33 +709,41 @@ impl Default for Config {
             allocated_size_blocks: AllocatedSizeBlocks::default(),
             list_owner: true,
             list_group: true,
+            columns: None,
+            grid_columns: None,
             list_current_and_parent_dirs: false,
             size_format: SizeFormat::default(),
-            sorting_order: SortingOrder::default(),
+            sort_keys: vec![SortKey::default()],
+            sort_args: true,
+            tabsize: 0,
             timestamp_used: TimestampUsed::default(),
+            extra_timestamps_used: Vec::new(),
+            time_style: TimeStyle::default(),
+            time_locale: None,
+            time_zone: TimeZoneSetting::default(),
+            show_tz_offset: false,
+            tree: false,
+            walk_order: WalkOrder::default(),
+            width: 80,
             icons: IconTheme::default(),
             theme: ThemeConfig::default(),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, Default)]
 pub enum AllocatedSizeBlocks {
+    #[default]
     Posix,
     Kibibytes,
     Raw,
 }
 
-impl Default for AllocatedSizeBlocks {
-    fn default() -> Self {
-        Self::Posix
-    }
-}
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum IndicatorStyle {
     Classify,
     Slash,
+    #[default]
     Never,
 }
 
@@ -373,12 +769,6 @@ impl IndicatorStyle {
     }
 }
 
-impl Default for IndicatorStyle {
-    fn default() -> Self {
-        Self::Never
-    }
-}
-
 #[derive(Debug, PartialEq, Eq)]
 pub enum ModeFormat {
     Pwsh,
@@ -414,8 +804,9 @@ impl Default for ModeFormat {
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Default)]
 pub enum SizeFormat {
+    #[default]
     Raw,
     HumanReadable,
     Iec,
@@ -428,62 +819,460 @@ impl SizeFormat {
     }
 }
 
-impl Default for SizeFormat {
-    fn default() -> Self {
-        Self::Raw
-    }
-}
-
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum SortingOrder {
+    /// Directory order, as set by '-U' and '-f'.
+    None,
+    #[default]
     FileName,
     Size,
     Timestamp,
+    Extension,
+    Version,
+    Width,
+    Inode,
+    Nlink,
+    Owner,
+    Group,
+    Created,
+    /// Directories first, then files, symlinks and other file types.
+    FileType,
 }
 
 impl SortingOrder {
+    /// Returns the sorting order named by a key of '--sort'.
+    pub fn from_word(word: &str) -> Option<Self> {
+        match word {
+            "none" => Some(Self::None),
+            "name" => Some(Self::FileName),
+            "size" => Some(Self::Size),
+            "time" => Some(Self::Timestamp),
+            "extension" | "ext" => Some(Self::Extension),
+            "version" => Some(Self::Version),
+            "width" => Some(Self::Width),
+            "inode" => Some(Self::Inode),
+            "nlink" => Some(Self::Nlink),
+            "owner" => Some(Self::Owner),
+            "group" => Some(Self::Group),
+            "btime" => Some(Self::Created),
+            "type" => Some(Self::FileType),
+            _ => None,
+        }
+    }
+
+    /// Returns true if sorting needs the size or number of hard links,
+    /// which are read from the file standard info on windows.
     #[cfg(windows)]
-    pub fn is_size(&self) -> bool {
-        *self == Self::Size
+    pub fn uses_file_standard_info(&self) -> bool {
+        matches!(self, Self::Size | Self::Nlink)
+    }
+
+    /// Returns true if sorting needs the owner or group,
+    /// which are read from the security info on windows.
+    #[cfg(windows)]
+    pub fn uses_security_info(&self) -> bool {
+        matches!(self, Self::Owner | Self::Group)
     }
 }
 
-impl Default for SortingOrder {
-    fn default() -> Self {
-        Self::FileName
+/// A key of the sort specification set by '--sort', e.g. `-size` in `type,ext,-size,name`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SortKey {
+    pub sorting_order: SortingOrder,
+    /// Reverses the ordering of this key only, as set by a leading '-'.
+    pub reverse: bool,
+}
+
+impl SortKey {
+    pub fn new(sorting_order: SortingOrder) -> Self {
+        Self {
+            sorting_order: sorting_order,
+            reverse: false,
+        }
+    }
+
+    /// Parses a comma separated list of sort keys e.g. `type,ext,-size,name`.
+    pub fn parse_list(list: &str) -> anyhow::Result<Vec<Self>> {
+        list.split(',')
+            .map(|word| {
+                let (word, reverse) = match word.strip_prefix('-') {
+                    Some(word) => (word, true),
+                    None => (word, false),
+                };
+
+                match SortingOrder::from_word(word) {
+                    Some(sorting_order) => Ok(Self {
+                        sorting_order: sorting_order,
+                        reverse: reverse,
+                    }),
+                    None => Err(anyhow::anyhow!(
+                        "'{}' is an invalid sort key for '--sort'\n\
+                         possible sort keys are ['none', 'name', 'size', 'time', 'extension', 'ext', 'version', 'width', 'inode', 'nlink', 'owner', 'group', 'btime', 'type'], \
+                         each optionally prefixed with '-' to reverse it",
+                        word
+                    )),
+                }
+            })
+            .collect()
     }
 }
 
-#[derive(Debug, PartialEq, Eq)]
+/// How entries are partitioned into groups before sorting, as set by
+/// '--group-directories-first' and '--group-by'.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    /// Directories, then all other entries.
+    Directories,
+    /// Directories, files, symlinks, then other file types.
+    FileType,
+    Extension,
+    Owner,
+}
+
+/// How file names are quoted, as set by '--quoting-style', '-b', '-N' and '-Q'.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum QuotingStyle {
+    /// File names as is.
+    #[default]
+    Literal,
+    /// Quoted with the quotation marks of the locale e.g. `‘a b’`, with C escapes.
+    Locale,
+    /// Quoted for POSIX shells only if needed e.g. `'a b'`.
+    Shell,
+    ShellAlways,
+    /// Like `Shell`, but with control characters escaped e.g. `'a'$'\n''b'`.
+    ShellEscape,
+    ShellEscapeAlways,
+    /// Quoted like a C string literal e.g. `"a\nb"`.
+    C,
+    /// Like `C` but without quotation marks and with spaces escaped e.g. `a\ b`.
+    Escape,
+}
+
+impl QuotingStyle {
+    pub fn from_word(word: &str) -> Option<Self> {
+        match word {
+            "literal" => Some(Self::Literal),
+            "locale" => Some(Self::Locale),
+            "shell" => Some(Self::Shell),
+            "shell-always" => Some(Self::ShellAlways),
+            "shell-escape" => Some(Self::ShellEscape),
+            "shell-escape-always" => Some(Self::ShellEscapeAlways),
+            "c" => Some(Self::C),
+            "escape" => Some(Self::Escape),
+            _ => None,
+        }
+    }
+
+    /// Returns the word of the quoting style accepted by '--quoting-style'.
+    pub fn word(&self) -> &'static str {
+        match self {
+            Self::Literal => "literal",
+            Self::Locale => "locale",
+            Self::Shell => "shell",
+            Self::ShellAlways => "shell-always",
+            Self::ShellEscape => "shell-escape",
+            Self::ShellEscapeAlways => "shell-escape-always",
+            Self::C => "c",
+            Self::Escape => "escape",
+        }
+    }
+}
+
+/// How file names are compared when sorting by name, as set by '--collate'.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Collation {
+    /// Byte order, thus uppercase before lowercase like the C locale.
+    #[default]
+    Bytes,
+    CaseInsensitive,
+    /// Like UTF-8 locales such as `en_US.UTF-8`, where case and punctuation
+    /// are only compared if names are otherwise equal and lowercase is ordered first.
+    Locale,
+    /// Byte order, but with runs of digits compared by their numeric value.
+    Natural,
+}
+
+impl Collation {
+    /// Returns the collation of the locale set by `LC_COLLATE`.
+    pub fn from_env() -> Self {
+        if is_posix_collation_locale() {
+            Self::Bytes
+        } else {
+            Self::Locale
+        }
+    }
+}
+
+/// The order directories are visited in when recursing, as set by '--walk'.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WalkOrder {
+    /// Each subdirectory is listed before the next subdirectory of its parent.
+    #[default]
+    DepthFirst,
+    /// All subdirectories at a depth are listed before those at the next depth.
+    BreadthFirst,
+}
+
+#[derive(Debug, PartialEq, Eq, Default)]
 pub enum OutputFormat {
+    #[default]
     SingleColumn,
     Vertical,
     Across,
     Long,
+    Json,
+    Ndjson,
+    Csv,
+    Tsv,
+    FormatString,
+    /// File names separated by `, ` and wrapped at the line width, as set by '-m'.
+    Commas,
+    /// File names each terminated by a NUL byte, as set by '--zero'.
+    Zero,
 }
 
 impl OutputFormat {
     pub fn is_long(&self) -> bool {
         *self == Self::Long
     }
+
+    /// Returns true if entries are listed in a grid, one per line or in long format.
+    pub fn is_grid_or_long(&self) -> bool {
+        matches!(
+            self,
+            Self::SingleColumn | Self::Vertical | Self::Across | Self::Long
+        )
+    }
+
+    /// Returns true if entries are serialized for other programs to consume,
+    /// in which case no directory headers or total lines are printed.
+    pub fn is_machine_readable(&self) -> bool {
+        matches!(self, Self::Json | Self::Ndjson | Self::Csv | Self::Tsv)
+    }
+
+    /// Returns the character that ends each line, including directory headers and total lines.
+    pub fn line_terminator(&self) -> char {
+        if *self == Self::Zero {
+            '\0'
+        } else {
+            '\n'
+        }
+    }
+
+    /// Returns true if the output format may display metadata beyond
+    /// inode number and allocated size.
+    #[cfg(windows)]
+    pub fn lists_metadata(&self) -> bool {
+        self.is_long() || self.is_machine_readable() || *self == Self::FormatString
+    }
 }
 
-impl Default for OutputFormat {
-    fn default() -> Self {
-        Self::SingleColumn
+/// Parses a line width, where `0` means the width is unlimited.
+fn parse_width(value: &str) -> Option<usize> {
+    match value.parse::<usize>() {
+        Ok(0) => Some(usize::MAX),
+        Ok(width) => Some(width),
+        Err(_) => None,
     }
 }
 
-#[derive(Debug)]
+/// The style timestamps are formatted with, as set by '--time-style'.
+#[derive(Debug, PartialEq, Eq, Default)]
+pub enum TimeStyle {
+    /// `%b %e %H:%M` for recent timestamps and `%b %e  %Y` for old timestamps,
+    /// with the month names and date order of the locale set by `LC_TIME`.
+    #[default]
+    Locale,
+    /// `%Y-%m-%d %H:%M:%S.%N %z`
+    FullIso,
+    /// `%Y-%m-%d %H:%M`
+    LongIso,
+    /// `%m-%d %H:%M` for recent timestamps and `%Y-%m-%d ` for old timestamps.
+    Iso,
+    /// Strftime formats for recent and old timestamps.
+    Format {
+        recent: CompactString,
+        old: CompactString,
+    },
+    /// The age of timestamps e.g. `3 minutes ago` or `in 5 hours`.
+    Relative,
+    /// The age of timestamps in compact form e.g. `3m` or `in 5h`.
+    RelativeCompact,
+}
+
+impl TimeStyle {
+    /// Parses a time style as accepted by GNU ls, returning `None` if it is invalid.
+    ///
+    /// A style prefixed with `posix-` only takes effect outside of the C/POSIX locale.
+    pub fn parse(time_style: &str) -> Option<Self> {
+        let time_style = match time_style.strip_prefix("posix-") {
+            Some(_) if is_posix_locale() => return Some(Self::Locale),
+            Some(time_style) => time_style,
+            None => time_style,
+        };
+
+        match time_style {
+            "full-iso" => Some(Self::FullIso),
+            "long-iso" => Some(Self::LongIso),
+            "iso" => Some(Self::Iso),
+            "locale" => Some(Self::Locale),
+            "relative" => Some(Self::Relative),
+            "relative-compact" => Some(Self::RelativeCompact),
+            _ => {
+                let format = time_style.strip_prefix('+')?;
+                let (recent, old) = match format.split_once('\n') {
+                    Some((_, old)) if old.contains('\n') => return None,
+                    Some((recent, old)) => (recent, old),
+                    None => (format, format),
+                };
+
+                let recent = to_chrono_strftime_format(recent);
+                let old = to_chrono_strftime_format(old);
+                if is_valid_strftime_format(&recent) && is_valid_strftime_format(&old) {
+                    Some(Self::Format {
+                        recent: recent,
+                        old: old,
+                    })
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+/// The time zone timestamps are converted to, as set by '--tz'.
+#[derive(Debug, PartialEq, Eq, Default)]
+pub enum TimeZoneSetting {
+    /// The local time zone, which honours the `TZ` environment variable.
+    #[default]
+    Local,
+    /// A time zone name from the tz database e.g. `Europe/Berlin`.
+    Named(CompactString),
+    Fixed(FixedOffset),
+}
+
+impl TimeZoneSetting {
+    /// Parses `UTC`, a time zone name or an offset such as `+05:30`, `-0800` or `+09`,
+    /// returning `None` if it is invalid.
+    pub fn parse(time_zone: &str) -> Option<Self> {
+        if time_zone.eq_ignore_ascii_case("utc") || time_zone == "Z" {
+            return FixedOffset::east_opt(0).map(Self::Fixed);
+        }
+
+        if let Some(offset_str) = time_zone.strip_prefix(['+', '-']) {
+            let (hours_str, minutes_str) = match offset_str.split_once(':') {
+                Some((hours_str, minutes_str)) => (hours_str, minutes_str),
+                None if offset_str.len() == 4 => offset_str.split_at(2),
+                None => (offset_str, "0"),
+            };
+            let hours: i32 = hours_str.parse().ok().filter(|hours| *hours <= 23)?;
+            let minutes: i32 = minutes_str.parse().ok().filter(|minutes| *minutes <= 59)?;
+            let offset_secs = hours * 3600 + minutes * 60;
+
+            return if time_zone.starts_with('-') {
+                FixedOffset::west_opt(offset_secs).map(Self::Fixed)
+            } else {
+                FixedOffset::east_opt(offset_secs).map(Self::Fixed)
+            };
+        }
+
+        if is_time_zone_name(time_zone) {
+            Some(Self::Named(CompactString::new(time_zone)))
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns true if `name` is a time zone in the tz database.
+#[cfg(unix)]
+fn is_time_zone_name(name: &str) -> bool {
+    use std::path::Path;
+
+    // The same locations searched by chrono when reading `TZ`
+    const TZDB_LOCATIONS: [&str; 4] = [
+        "/usr/share/zoneinfo",
+        "/share/zoneinfo",
+        "/etc/zoneinfo",
+        "/usr/share/lib/zoneinfo",
+    ];
+
+    if name.is_empty() || name.starts_with('/') || name.split('/').any(|part| part == "..") {
+        return false;
+    }
+
+    TZDB_LOCATIONS
+        .iter()
+        .any(|tzdb_location| Path::new(tzdb_location).join(name).is_file())
+}
+
+/// Time zone names are only resolved through `TZ` on unix.
+#[cfg(not(unix))]
+fn is_time_zone_name(_name: &str) -> bool {
+    false
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum TimestampUsed {
     Accessed,
     Changed,
     Created,
+    #[default]
     Modified,
 }
 
-impl Default for TimestampUsed {
-    fn default() -> Self {
-        Self::Modified
+impl TimestampUsed {
+    /// Returns the timestamp named by an argument of '--time'.
+    pub fn from_word(word: &str) -> Option<Self> {
+        match word {
+            "accessed" | "atime" => Some(Self::Accessed),
+            "changed" | "ctime" => Some(Self::Changed),
+            "created" | "btime" => Some(Self::Created),
+            "modified" | "mtime" => Some(Self::Modified),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_zone_setting_parse() {
+        assert_eq!(
+            TimeZoneSetting::parse("utc"),
+            Some(TimeZoneSetting::Fixed(FixedOffset::east_opt(0).unwrap()))
+        );
+        assert_eq!(
+            TimeZoneSetting::parse("+05:30"),
+            Some(TimeZoneSetting::Fixed(
+                FixedOffset::east_opt(5 * 3600 + 30 * 60).unwrap()
+            ))
+        );
+        assert_eq!(
+            TimeZoneSetting::parse("-0800"),
+            Some(TimeZoneSetting::Fixed(
+                FixedOffset::west_opt(8 * 3600).unwrap()
+            ))
+        );
+        assert_eq!(
+            TimeZoneSetting::parse("+09"),
+            Some(TimeZoneSetting::Fixed(
+                FixedOffset::east_opt(9 * 3600).unwrap()
+            ))
+        );
+        assert_eq!(TimeZoneSetting::parse("+25:00"), None);
+        assert_eq!(TimeZoneSetting::parse("+05:75"), None);
+        assert_eq!(TimeZoneSetting::parse("../etc/passwd"), None);
+        assert_eq!(TimeZoneSetting::parse("Not/A_Zone"), None);
+        if cfg!(unix) && std::path::Path::new("/usr/share/zoneinfo/Asia/Tokyo").is_file() {
+            assert_eq!(
+                TimeZoneSetting::parse("Asia/Tokyo"),
+                Some(TimeZoneSetting::Named(CompactString::new("Asia/Tokyo")))
+            );
+        }
     }
 }
@@ -1,3 +1,4 @@
+use std::ffi::{OsStr, OsString};
 use std::fs::Metadata;
 #[cfg(unix)]
 use std::os::unix::fs::{FileTypeExt, MetadataExt};
@@ -5,8 +6,7 @@ use std::os::unix::fs::{FileTypeExt, MetadataExt};
 use std::os::windows::fs::MetadataExt;
 use std::path::{Path, PathBuf};
 
-use compact_str::{CompactString, ToCompactString};
-use nls_term_grid::Alignment;
+use compact_str::CompactString;
 
 use crate::config::{Config, TimestampUsed};
 #[cfg(unix)]
@@ -14,17 +14,20 @@ use crate::os::unix::*;
 #[cfg(windows)]
 use crate::os::windows::*;
 use crate::output::*;
-use crate::utils::systemtime_to_unix_timestamp;
+use crate::utils::{os_str_bytes, systemtime_to_unix_timestamp, UnixTimestamp};
 
 #[derive(Debug, Default)]
 pub struct EntryBuf {
+    /// The file name, where bytes that are not valid UTF-8 are replaced with U+FFFD.
+    /// It is printed from `raw_file_name` instead, so that such bytes are written as is.
     file_name: CompactString,
-    file_name_key: String,
+    raw_file_name: OsString,
+    file_name_key: Vec<u8>,
     path: PathBuf,
     metadata: Option<Metadata>,
     allocated_size: Option<u64>,
     size: Option<u64>,
-    timestamp: Option<i64>,
+    timestamp: Option<UnixTimestamp>,
     #[cfg(unix)]
     ino: Option<u64>,
     #[cfg(windows)]
@@ -35,11 +38,12 @@ pub struct EntryBuf {
 
 impl EntryBuf {
     pub fn from_direntry(dent: ignore::DirEntry, config: &Config) -> Self {
-        let file_name = if dent.depth() == 0 {
-            CompactString::new_inline(".")
+        let raw_file_name = if dent.depth() == 0 {
+            OsString::from(".")
         } else {
-            dent.file_name().to_string_lossy().to_compact_string()
+            dent.file_name().to_os_string()
         };
+        let file_name = CompactString::from(raw_file_name.to_string_lossy());
 
         let follow_links = if cfg!(windows) {
             dent.path_is_symlink() && config.dereference
@@ -69,6 +73,7 @@ impl EntryBuf {
 
         let mut entrybuf = Self {
             file_name: file_name,
+            raw_file_name: raw_file_name,
             path: dent.into_path(),
             metadata: metadata,
             #[cfg(unix)]
@@ -83,7 +88,7 @@ impl EntryBuf {
     }
 
     pub fn from_cmdline_path(path: &Path, config: &Config) -> Self {
-        let file_name = path.display().to_compact_string();
+        let file_name = CompactString::from(path.as_os_str().to_string_lossy());
         let metadata_result = if config.dereference_cmdline_symlink {
             path.metadata()
         } else {
@@ -99,6 +104,7 @@ impl EntryBuf {
 
         let mut entrybuf = Self {
             file_name: file_name,
+            raw_file_name: path.as_os_str().to_os_string(),
             path: path.to_path_buf(),
             metadata: metadata,
             #[cfg(windows)]
@@ -126,6 +132,7 @@ impl EntryBuf {
 
         let mut entrybuf = Self {
             file_name: CompactString::from(path_name),
+            raw_file_name: OsString::from(path_name),
             path: path.to_path_buf(),
             metadata: metadata,
             #[cfg(windows)]
@@ -138,7 +145,7 @@ impl EntryBuf {
     }
 
     pub fn init(&mut self, config: &Config) {
-        self.file_name_key = self.file_name.to_lowercase();
+        self.file_name_key = collation_key(&os_str_bytes(&self.raw_file_name), config);
         if let Some(metadata) = &self.metadata {
             self.size = Some(metadata.len());
         }
@@ -159,24 +166,14 @@ impl EntryBuf {
             self.ino = Some(metadata.ino());
             self.allocated_size = Some(get_allocated_size(metadata, config));
 
-            self.timestamp = match config.timestamp_used {
-                TimestampUsed::Accessed => Some(metadata.atime()),
-                TimestampUsed::Changed => Some(metadata.ctime()),
-                TimestampUsed::Created => systemtime_to_unix_timestamp(metadata.created()),
-                TimestampUsed::Modified => Some(metadata.mtime()),
-            };
+            self.timestamp = get_timestamp(metadata, &config.timestamp_used);
         }
     }
 
     #[cfg(windows)]
     fn init_windows(&mut self, config: &Config) {
         if let Some(metadata) = &self.metadata {
-            self.timestamp = match config.timestamp_used {
-                TimestampUsed::Accessed => systemtime_to_unix_timestamp(metadata.accessed()),
-                TimestampUsed::Changed => None,
-                TimestampUsed::Created => systemtime_to_unix_timestamp(metadata.created()),
-                TimestampUsed::Modified => systemtime_to_unix_timestamp(metadata.modified()),
-            };
+            self.timestamp = get_timestamp(metadata, &config.timestamp_used);
         }
 
         self.windows_metadata = WindowsMetadata::get(&self.path, self.follow_links, config);
@@ -190,26 +187,53 @@ impl EntryBuf {
     #[cfg(not(any(unix, windows)))]
     fn init_others(&mut self, config: &Config) {
         if let Some(metadata) = &self.metadata {
-            self.timestamp = match config.timestamp_used {
-                TimestampUsed::Accessed => systemtime_to_unix_timestamp(metadata.accessed()),
-                TimestampUsed::Changed => None,
-                TimestampUsed::Created => systemtime_to_unix_timestamp(metadata.created()),
-                TimestampUsed::Modified => systemtime_to_unix_timestamp(metadata.modified()),
-            };
+            self.timestamp = get_timestamp(metadata, &config.timestamp_used);
         }
     }
 
-    pub fn file_name_key(&self) -> &str {
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    /// Returns the file name as it was read from the file system.
+    pub fn raw_file_name(&self) -> &OsStr {
+        &self.raw_file_name
+    }
+
+    pub fn file_name_key(&self) -> &[u8] {
         &self.file_name_key
     }
 
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn metadata(&self) -> Option<&Metadata> {
+        self.metadata.as_ref()
+    }
+
     pub fn file_name_cell(&self, config: &Config) -> GridCell {
         match &self.metadata {
-            Some(metadata) => format_filename(&self.path, &self.file_name, metadata, config),
-            None => GridCell::from_str_with_style(&self.file_name, None),
+            Some(metadata) => format_filename(&self.path, &self.raw_file_name, metadata, config),
+            None => quote_file_name(&self.raw_file_name, config),
         }
     }
 
+    #[cfg(unix)]
+    pub fn ino(&self) -> Option<u64> {
+        self.ino
+    }
+
+    #[cfg(windows)]
+    pub fn ino(&self) -> Option<u128> {
+        get_file_id_identifier(&self.path, self.follow_links).ok()
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    pub fn ino(&self) -> Option<u64> {
+        None
+    }
+
     #[cfg(unix)]
     pub fn ino_cell(&self, config: &Config) -> GridCell {
         let inode_style = config.theme.inode_style();
@@ -314,6 +338,21 @@ impl EntryBuf {
         }
     }
 
+    #[cfg(unix)]
+    pub fn nlink(&self) -> Option<u64> {
+        self.metadata.as_ref().map(|metadata| metadata.nlink())
+    }
+
+    #[cfg(windows)]
+    pub fn nlink(&self) -> Option<u64> {
+        self.windows_metadata.nlink()
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    pub fn nlink(&self) -> Option<u64> {
+        self.metadata.as_ref().map(|_| 1)
+    }
+
     #[cfg(unix)]
     pub fn nlink_cell(&self, config: &Config) -> GridCell {
         let nlink_style = config.theme.nlink_style();
@@ -337,6 +376,23 @@ impl EntryBuf {
         }
     }
 
+    #[cfg(unix)]
+    pub fn owner_name(&self) -> Option<CompactString> {
+        self.metadata
+            .as_ref()
+            .and_then(|metadata| get_username_by_uid(metadata.uid()))
+    }
+
+    #[cfg(windows)]
+    pub fn owner_name(&self) -> Option<CompactString> {
+        self.windows_metadata.owner_name().map(CompactString::from)
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    pub fn owner_name(&self) -> Option<CompactString> {
+        None
+    }
+
     #[cfg(unix)]
     pub fn owner_cell(&self, config: &Config) -> GridCell {
         match &self.metadata {
@@ -359,6 +415,23 @@ impl EntryBuf {
         }
     }
 
+    #[cfg(unix)]
+    pub fn group_name(&self) -> Option<CompactString> {
+        self.metadata
+            .as_ref()
+            .and_then(|metadata| get_groupname_by_gid(metadata.gid()))
+    }
+
+    #[cfg(windows)]
+    pub fn group_name(&self) -> Option<CompactString> {
+        self.windows_metadata.group_name().map(CompactString::from)
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    pub fn group_name(&self) -> Option<CompactString> {
+        None
+    }
+
     #[cfg(unix)]
     pub fn group_cell(&self, config: &Config) -> GridCell {
         match &self.metadata {
@@ -400,10 +473,18 @@ impl EntryBuf {
         }
     }
 
-    pub fn timestamp(&self) -> Option<i64> {
+    pub fn timestamp(&self) -> Option<UnixTimestamp> {
         self.timestamp
     }
 
+    /// Returns the timestamp given by `timestamp_used`, which may differ
+    /// from the timestamp configured for sorting and long format.
+    pub fn timestamp_of(&self, timestamp_used: &TimestampUsed) -> Option<UnixTimestamp> {
+        self.metadata
+            .as_ref()
+            .and_then(|metadata| get_timestamp(metadata, timestamp_used))
+    }
+
     pub fn timestamp_cell(&self, config: &Config) -> GridCell {
         match &self.timestamp {
             Some(timestamp) => format_timestamp(*timestamp, config),
@@ -411,3 +492,32 @@ impl EntryBuf {
         }
     }
 }
+
+#[cfg(unix)]
+fn get_timestamp(metadata: &Metadata, timestamp_used: &TimestampUsed) -> Option<UnixTimestamp> {
+    match timestamp_used {
+        TimestampUsed::Accessed => Some(UnixTimestamp::new(
+            metadata.atime(),
+            metadata.atime_nsec() as u32,
+        )),
+        TimestampUsed::Changed => Some(UnixTimestamp::new(
+            metadata.ctime(),
+            metadata.ctime_nsec() as u32,
+        )),
+        TimestampUsed::Created => systemtime_to_unix_timestamp(metadata.created()),
+        TimestampUsed::Modified => Some(UnixTimestamp::new(
+            metadata.mtime(),
+            metadata.mtime_nsec() as u32,
+        )),
+    }
+}
+
+#[cfg(not(unix))]
+fn get_timestamp(metadata: &Metadata, timestamp_used: &TimestampUsed) -> Option<UnixTimestamp> {
+    match timestamp_used {
+        TimestampUsed::Accessed => systemtime_to_unix_timestamp(metadata.accessed()),
+        TimestampUsed::Changed => None,
+        TimestampUsed::Created => systemtime_to_unix_timestamp(metadata.created()),
+        TimestampUsed::Modified => systemtime_to_unix_timestamp(metadata.modified()),
+    }
+}
@@ -1,31 +1,22 @@
-use std::path::Path;
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
 use std::process;
 
+use compact_str::{format_compact, CompactString};
 use ignore::overrides::OverrideBuilder;
 use ignore::{Walk, WalkBuilder};
 
-use crate::config::Config;
+use crate::config::{Config, WalkOrder};
 use crate::entry::EntryBuf;
-use crate::output::{output, print_total};
+use crate::output::{output, print_dir_header, print_total, sort_entrybuf_vec, tree_format};
 
 pub fn list_dir(path: &Path, config: &Config) -> Result<(), ()> {
-    let mut entrybuf_vec: Vec<EntryBuf> = Vec::with_capacity(16);
+    list_dir_entries(path, config).map(|_| ())
+}
 
-    for result in walk_dir(path, config) {
-        match result {
-            Ok(dent) => {
-                if dent.depth() != 0 {
-                    entrybuf_vec.push(EntryBuf::from_direntry(dent, config));
-                }
-            }
-            Err(err) => {
-                eprintln!("nls: {}", err);
-                if !err.is_partial() && err.is_io() {
-                    return Err(());
-                }
-            }
-        }
-    }
+/// Like [`list_dir`], but returns the listed entries in the order they were listed.
+fn list_dir_entries(path: &Path, config: &Config) -> Result<Vec<EntryBuf>, ()> {
+    let mut entrybuf_vec = read_dir(path, config)?;
 
     if config.list_current_and_parent_dirs {
         entrybuf_vec.push(EntryBuf::from_named_path(".", path, config));
@@ -37,57 +28,236 @@ pub fn list_dir(path: &Path, config: &Config) -> Result<(), ()> {
         print_total(&entrybuf_vec, config);
     }
 
-    output(&mut entrybuf_vec, config);
+    output(&mut entrybuf_vec, Some(path), config);
 
-    Ok(())
+    Ok(entrybuf_vec)
 }
 
+/// Lists a directory and its subdirectories, which are visited in the order
+/// they are listed, thus following the sorting order.
 pub fn recursive_list_dir(path: &Path, config: &Config) {
-    if list_dir(path, config).is_ok() {
-        for result in recursive_walk_dir(path, config) {
-            match result {
-                Ok(dent) => {
-                    if dent.depth() != 0 {
-                        println!("\n{}:", dent.path().display());
-                        let _ = list_dir(dent.path(), config);
+    if config.tree {
+        tree_list_dir(path, config);
+        return;
+    }
+
+    let mut ancestor_vec: Vec<PathBuf> = Vec::with_capacity(8);
+    if config.dereference {
+        ancestor_vec.extend(path.canonicalize().ok());
+    }
+
+    match config.walk_order {
+        WalkOrder::DepthFirst => depth_first_list_dir(path, 0, &mut ancestor_vec, config),
+        WalkOrder::BreadthFirst => breadth_first_list_dir(path, ancestor_vec, config),
+    }
+}
+
+fn depth_first_list_dir(
+    path: &Path,
+    depth: usize,
+    ancestor_vec: &mut Vec<PathBuf>,
+    config: &Config,
+) {
+    let entrybuf_vec = match list_dir_entries(path, config) {
+        Ok(entrybuf_vec) => entrybuf_vec,
+        Err(_) => return,
+    };
+    if config.max_depth.is_some_and(|max_depth| depth >= max_depth) {
+        return;
+    }
+
+    for subdir_path in subdir_paths(&entrybuf_vec) {
+        if config.dereference {
+            match canonical_dir_path(subdir_path, ancestor_vec) {
+                Some(canonical_path) => ancestor_vec.push(canonical_path),
+                None => continue,
+            }
+        }
+
+        print_dir_header(subdir_path, false, config);
+        depth_first_list_dir(subdir_path, depth + 1, ancestor_vec, config);
+
+        if config.dereference {
+            ancestor_vec.pop();
+        }
+    }
+}
+
+/// Lists directories level by level, where each queued directory
+/// keeps its own ancestors to detect directory loops when dereferencing.
+fn breadth_first_list_dir(path: &Path, ancestor_vec: Vec<PathBuf>, config: &Config) {
+    let mut dir_queue: VecDeque<(PathBuf, usize, Vec<PathBuf>)> = VecDeque::with_capacity(16);
+    dir_queue.push_back((path.to_path_buf(), 0, ancestor_vec));
+
+    let mut is_root = true;
+    while let Some((dir_path, depth, ancestor_vec)) = dir_queue.pop_front() {
+        if !is_root {
+            print_dir_header(&dir_path, false, config);
+        }
+        is_root = false;
+
+        let entrybuf_vec = match list_dir_entries(&dir_path, config) {
+            Ok(entrybuf_vec) => entrybuf_vec,
+            Err(_) => continue,
+        };
+        if config.max_depth.is_some_and(|max_depth| depth >= max_depth) {
+            continue;
+        }
+
+        for subdir_path in subdir_paths(&entrybuf_vec) {
+            let mut subdir_ancestor_vec = Vec::new();
+            if config.dereference {
+                match canonical_dir_path(subdir_path, &ancestor_vec) {
+                    Some(canonical_path) => {
+                        subdir_ancestor_vec.reserve(ancestor_vec.len() + 1);
+                        subdir_ancestor_vec.extend_from_slice(&ancestor_vec);
+                        subdir_ancestor_vec.push(canonical_path);
                     }
-                }
-                Err(err) => {
-                    eprintln!("nls: {}", err);
+                    None => continue,
                 }
             }
+
+            dir_queue.push_back((subdir_path.to_path_buf(), depth + 1, subdir_ancestor_vec));
         }
     }
 }
 
-fn walk_dir(path: &Path, config: &Config) -> Walk {
-    let mut override_builder = OverrideBuilder::new(path);
-    for ignore_glob in &config.ignore_glob_vec {
-        if let Err(err) = override_builder.add(ignore_glob) {
-            eprintln!("nls: error with ignore-glob '{}': {}", ignore_glob, err);
-            process::exit(1);
+/// Returns the paths of the subdirectories among listed entries, excluding `.` and `..`.
+fn subdir_paths(entrybuf_vec: &[EntryBuf]) -> impl Iterator<Item = &Path> {
+    entrybuf_vec
+        .iter()
+        .filter(|entrybuf| {
+            let is_dir = entrybuf
+                .metadata()
+                .map(|metadata| metadata.is_dir())
+                .unwrap_or(false);
+
+            is_dir && !matches!(entrybuf.file_name(), "." | "..")
+        })
+        .map(|entrybuf| entrybuf.path())
+}
+
+/// Returns the canonical path of a directory to recurse into when dereferencing, or `None`
+/// if it is one of its ancestors, as symlinks to directories are only followed when
+/// dereferencing, so this is the only case where a directory loop can occur.
+fn canonical_dir_path(dir_path: &Path, ancestor_vec: &[PathBuf]) -> Option<PathBuf> {
+    let canonical_path = dir_path.canonicalize().ok()?;
+    if ancestor_vec.contains(&canonical_path) {
+        eprintln!(
+            "nls: '{}': not listing already-listed directory",
+            dir_path.display()
+        );
+        return None;
+    }
+
+    Some(canonical_path)
+}
+
+/// Lists a directory and its subdirectories as a tree.
+fn tree_list_dir(path: &Path, config: &Config) {
+    let mut entrybuf_vec = vec![EntryBuf::from_cmdline_path(path, config)];
+    let mut prefix_vec = vec![CompactString::default()];
+    let mut ancestor_vec: Vec<PathBuf> = Vec::with_capacity(8);
+    if config.dereference {
+        ancestor_vec.extend(path.canonicalize().ok());
+    }
+
+    inner_tree_list_dir(
+        path,
+        0,
+        "",
+        &mut entrybuf_vec,
+        &mut prefix_vec,
+        &mut ancestor_vec,
+        config,
+    );
+
+    tree_format(&entrybuf_vec, &prefix_vec, config);
+}
+
+fn inner_tree_list_dir(
+    path: &Path,
+    depth: usize,
+    parent_prefix: &str,
+    entrybuf_vec: &mut Vec<EntryBuf>,
+    prefix_vec: &mut Vec<CompactString>,
+    ancestor_vec: &mut Vec<PathBuf>,
+    config: &Config,
+) {
+    let mut children_vec = match read_dir(path, config) {
+        Ok(children_vec) => children_vec,
+        Err(_) => return,
+    };
+    sort_entrybuf_vec(&mut children_vec, config);
+
+    let num_children = children_vec.len();
+    for (i, entrybuf) in children_vec.into_iter().enumerate() {
+        let is_last = (i + 1) == num_children;
+        let (branch, child_prefix) = if is_last {
+            ("└── ", format_compact!("{}    ", parent_prefix))
+        } else {
+            ("├── ", format_compact!("{}│   ", parent_prefix))
+        };
+        let is_dir = entrybuf
+            .metadata()
+            .map(|metadata| metadata.is_dir())
+            .unwrap_or(false);
+        let child_path = entrybuf.path().to_path_buf();
+
+        entrybuf_vec.push(entrybuf);
+        prefix_vec.push(format_compact!("{}{}", parent_prefix, branch));
+
+        if !is_dir || config.max_depth.is_some_and(|max_depth| depth >= max_depth) {
+            continue;
+        }
+
+        if config.dereference {
+            match canonical_dir_path(&child_path, ancestor_vec) {
+                Some(canonical_path) => ancestor_vec.push(canonical_path),
+                None => continue,
+            }
+        }
+
+        inner_tree_list_dir(
+            &child_path,
+            depth + 1,
+            &child_prefix,
+            entrybuf_vec,
+            prefix_vec,
+            ancestor_vec,
+            config,
+        );
+
+        if config.dereference {
+            ancestor_vec.pop();
         }
     }
-    match override_builder.build() {
-        Ok(overrides) => WalkBuilder::new(path)
-            .hidden(config.ignore_hidden)
-            .parents(config.git_ignore || config.ignore_file)
-            .ignore(config.ignore_file)
-            .git_exclude(config.git_ignore)
-            .git_global(config.git_ignore)
-            .git_ignore(config.git_ignore)
-            .follow_links(config.dereference)
-            .max_depth(Some(1))
-            .overrides(overrides)
-            .build(),
-        Err(err) => {
-            eprintln!("nls: unable to build override builder: {}", err);
-            process::exit(1);
+}
+
+/// Reads the entries of a directory, excluding the directory itself.
+fn read_dir(path: &Path, config: &Config) -> Result<Vec<EntryBuf>, ()> {
+    let mut entrybuf_vec: Vec<EntryBuf> = Vec::with_capacity(16);
+
+    for result in walk_dir(path, config) {
+        match result {
+            Ok(dent) => {
+                if dent.depth() != 0 {
+                    entrybuf_vec.push(EntryBuf::from_direntry(dent, config));
+                }
+            }
+            Err(err) => {
+                eprintln!("nls: {}", err);
+                if !err.is_partial() && err.is_io() {
+                    return Err(());
+                }
+            }
         }
     }
+
+    Ok(entrybuf_vec)
 }
 
-fn recursive_walk_dir(path: &Path, config: &Config) -> Walk {
+fn walk_dir(path: &Path, config: &Config) -> Walk {
     let mut override_builder = OverrideBuilder::new(path);
     for ignore_glob in &config.ignore_glob_vec {
         if let Err(err) = override_builder.add(ignore_glob) {
@@ -104,14 +274,8 @@ fn recursive_walk_dir(path: &Path, config: &Config) -> Walk {
             .git_global(config.git_ignore)
             .git_ignore(config.git_ignore)
             .follow_links(config.dereference)
-            .max_depth(config.max_depth)
+            .max_depth(Some(1))
             .overrides(overrides)
-            .sort_by_file_path(|path1, path2| path1.cmp(path2))
-            .filter_entry(|dent| {
-                dent.file_type()
-                    .map(|file_type| file_type.is_dir())
-                    .unwrap_or(false)
-            })
             .build(),
         Err(err) => {
             eprintln!("nls: unable to build override builder: {}", err);
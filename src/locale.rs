@@ -0,0 +1,293 @@
+use chrono::{DateTime, Datelike, TimeZone, Timelike};
+use compact_str::{format_compact, CompactString};
+use unicode_width::UnicodeWidthStr;
+
+/// Month names and date ordering of a locale, used to format timestamps
+/// with the default time style.
+#[derive(Debug, PartialEq, Eq)]
+pub struct TimeLocale {
+    language: &'static str,
+    abmon: [&'static str; 12],
+    date_order: DateOrder,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DateOrder {
+    /// e.g. `Okt 17` and `10月 17`
+    MonthDay,
+    /// e.g. `17 oct.`
+    DayMonth,
+    /// e.g. `17. Okt`
+    DayDotMonth,
+}
+
+impl TimeLocale {
+    /// Built-in tables of abbreviated month names, as found in glibc's `LC_TIME`.
+    const TABLES: &'static [Self] = &[
+        Self {
+            language: "da",
+            abmon: [
+                "jan", "feb", "mar", "apr", "maj", "jun", "jul", "aug", "sep", "okt", "nov", "dec",
+            ],
+            date_order: DateOrder::DayDotMonth,
+        },
+        Self {
+            language: "de",
+            abmon: [
+                "Jan", "Feb", "Mär", "Apr", "Mai", "Jun", "Jul", "Aug", "Sep", "Okt", "Nov", "Dez",
+            ],
+            date_order: DateOrder::DayDotMonth,
+        },
+        Self {
+            language: "es",
+            abmon: [
+                "ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sep", "oct", "nov", "dic",
+            ],
+            date_order: DateOrder::DayMonth,
+        },
+        Self {
+            language: "fr",
+            abmon: [
+                "janv.", "févr.", "mars", "avril", "mai", "juin", "juil.", "août", "sept.", "oct.",
+                "nov.", "déc.",
+            ],
+            date_order: DateOrder::DayMonth,
+        },
+        Self {
+            language: "it",
+            abmon: [
+                "gen", "feb", "mar", "apr", "mag", "giu", "lug", "ago", "set", "ott", "nov", "dic",
+            ],
+            date_order: DateOrder::DayMonth,
+        },
+        Self {
+            language: "ja",
+            abmon: [
+                "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月",
+                "12月",
+            ],
+            date_order: DateOrder::MonthDay,
+        },
+        Self {
+            language: "nb",
+            abmon: [
+                "jan", "feb", "mar", "apr", "mai", "jun", "jul", "aug", "sep", "okt", "nov", "des",
+            ],
+            date_order: DateOrder::DayDotMonth,
+        },
+        Self {
+            language: "nl",
+            abmon: [
+                "jan", "feb", "mrt", "apr", "mei", "jun", "jul", "aug", "sep", "okt", "nov", "dec",
+            ],
+            date_order: DateOrder::DayMonth,
+        },
+        Self {
+            language: "pl",
+            abmon: [
+                "sty", "lut", "mar", "kwi", "maj", "cze", "lip", "sie", "wrz", "paź", "lis", "gru",
+            ],
+            date_order: DateOrder::DayMonth,
+        },
+        Self {
+            language: "pt",
+            abmon: [
+                "jan", "fev", "mar", "abr", "mai", "jun", "jul", "ago", "set", "out", "nov", "dez",
+            ],
+            date_order: DateOrder::DayMonth,
+        },
+        Self {
+            language: "ru",
+            abmon: [
+                "янв", "фев", "мар", "апр", "мая", "июн", "июл", "авг", "сен", "окт", "ноя", "дек",
+            ],
+            date_order: DateOrder::DayMonth,
+        },
+        Self {
+            language: "sv",
+            abmon: [
+                "jan", "feb", "mar", "apr", "maj", "jun", "jul", "aug", "sep", "okt", "nov", "dec",
+            ],
+            date_order: DateOrder::DayMonth,
+        },
+        Self {
+            language: "zh",
+            abmon: [
+                "1月", "2月", "3月", "4月", "5月", "6月", "7月", "8月", "9月", "10月", "11月",
+                "12月",
+            ],
+            date_order: DateOrder::MonthDay,
+        },
+    ];
+
+    /// Returns the time locale set by `LC_ALL`, `LC_TIME` or `LANG`.
+    ///
+    /// `None` is returned for the C/POSIX and English locales and for locales without
+    /// a built-in table, in which case English month names are used.
+    pub fn from_env() -> Option<&'static Self> {
+        Self::from_locale_name(&locale_name()?)
+    }
+
+    /// Returns the time locale of a locale name such as `de_DE.UTF-8`.
+    pub fn from_locale_name(locale_name: &str) -> Option<&'static Self> {
+        let language = locale_name
+            .split(['_', '.', '@'])
+            .next()
+            .unwrap_or(locale_name);
+
+        Self::TABLES
+            .iter()
+            .find(|time_locale| time_locale.language == language)
+    }
+
+    /// Formats a datetime like `%b %e %H:%M` for recent timestamps and like
+    /// `%b %e  %Y` for old timestamps, in the month names and date order of this locale.
+    ///
+    /// Month names are padded to the same width so that timestamps stay aligned.
+    pub fn format<Tz: TimeZone>(&self, datetime: &DateTime<Tz>, is_recent: bool) -> CompactString {
+        let month = self.abmon[datetime.month0() as usize];
+        let month_width = self
+            .abmon
+            .iter()
+            .map(|month| UnicodeWidthStr::width(*month))
+            .max()
+            .unwrap_or(0);
+        let month_padding = month_width - UnicodeWidthStr::width(month);
+
+        let date = match self.date_order {
+            DateOrder::MonthDay => format_compact!(
+                "{}{:pad$} {:>2}",
+                month,
+                "",
+                datetime.day(),
+                pad = month_padding
+            ),
+            DateOrder::DayMonth => format_compact!(
+                "{:>2} {}{:pad$}",
+                datetime.day(),
+                month,
+                "",
+                pad = month_padding
+            ),
+            DateOrder::DayDotMonth => format_compact!(
+                "{:>2}. {}{:pad$}",
+                datetime.day(),
+                month,
+                "",
+                pad = month_padding
+            ),
+        };
+
+        if is_recent {
+            format_compact!("{} {:02}:{:02}", date, datetime.hour(), datetime.minute())
+        } else {
+            format_compact!("{}  {:04}", date, datetime.year())
+        }
+    }
+}
+
+/// Returns true if the locale used for timestamps is the C/POSIX locale.
+pub fn is_posix_locale() -> bool {
+    is_posix_locale_name(locale_name())
+}
+
+/// Returns true if the locale used to collate file names is the C/POSIX locale.
+pub fn is_posix_collation_locale() -> bool {
+    is_posix_locale_name(locale_name_of("LC_COLLATE"))
+}
+
+/// Returns true if the character set of the locale set by `LC_CTYPE` is UTF-8.
+pub fn is_utf8_locale() -> bool {
+    match locale_name_of("LC_CTYPE") {
+        Some(locale_name) => {
+            let locale_name = locale_name.to_ascii_lowercase();
+            locale_name.contains("utf-8") || locale_name.contains("utf8")
+        }
+        None => false,
+    }
+}
+
+/// Returns true if a locale name such as `C.UTF-8` names the C/POSIX locale,
+/// ignoring its `.codeset` and `@modifier` suffixes.
+fn is_posix_locale_name(locale_name: Option<String>) -> bool {
+    match locale_name {
+        Some(locale_name) => {
+            let language = locale_name.split(['.', '@']).next().unwrap_or_default();
+            language == "C" || language == "POSIX"
+        }
+        None => true,
+    }
+}
+
+/// Returns the locale used for timestamps, which is the first of
+/// `LC_ALL`, `LC_TIME` and `LANG` that is set and not empty.
+fn locale_name() -> Option<String> {
+    locale_name_of("LC_TIME")
+}
+
+/// Returns the locale of a category such as `LC_TIME`, which is the first of
+/// `LC_ALL`, the category and `LANG` that is set and not empty.
+fn locale_name_of(category: &str) -> Option<String> {
+    ["LC_ALL", category, "LANG"]
+        .iter()
+        .filter_map(std::env::var_os)
+        .find(|value| !value.is_empty())
+        .map(|value| value.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::FixedOffset;
+
+    #[test]
+    fn test_time_locale_from_locale_name() {
+        assert_eq!(
+            TimeLocale::from_locale_name("de_DE.UTF-8").map(|time_locale| time_locale.language),
+            Some("de")
+        );
+        assert_eq!(
+            TimeLocale::from_locale_name("fr_FR@euro").map(|time_locale| time_locale.language),
+            Some("fr")
+        );
+        assert_eq!(
+            TimeLocale::from_locale_name("ja").map(|time_locale| time_locale.language),
+            Some("ja")
+        );
+        assert_eq!(TimeLocale::from_locale_name("C"), None);
+        assert_eq!(TimeLocale::from_locale_name("POSIX"), None);
+        assert_eq!(TimeLocale::from_locale_name("C.UTF-8"), None);
+        assert_eq!(TimeLocale::from_locale_name("en_US.UTF-8"), None);
+        assert_eq!(TimeLocale::from_locale_name("xx_XX"), None);
+    }
+
+    #[test]
+    fn test_is_posix_locale_name() {
+        assert!(is_posix_locale_name(None));
+        assert!(is_posix_locale_name(Some("C".to_string())));
+        assert!(is_posix_locale_name(Some("POSIX".to_string())));
+        assert!(is_posix_locale_name(Some("C.UTF-8".to_string())));
+        assert!(is_posix_locale_name(Some("C.utf8".to_string())));
+        assert!(!is_posix_locale_name(Some("en_US.UTF-8".to_string())));
+        assert!(!is_posix_locale_name(Some("Ca_ES".to_string())));
+    }
+
+    #[test]
+    fn test_time_locale_format() {
+        let datetime = FixedOffset::east_opt(0)
+            .unwrap()
+            .with_ymd_and_hms(2024, 3, 5, 9, 7, 0)
+            .unwrap();
+
+        let de = TimeLocale::from_locale_name("de_DE").unwrap();
+        assert_eq!(de.format(&datetime, true), " 5. Mär 09:07");
+        assert_eq!(de.format(&datetime, false), " 5. Mär  2024");
+
+        let fr = TimeLocale::from_locale_name("fr_FR").unwrap();
+        assert_eq!(fr.format(&datetime, true), " 5 mars  09:07");
+
+        let ja = TimeLocale::from_locale_name("ja_JP").unwrap();
+        assert_eq!(ja.format(&datetime, false), "3月   5  2024");
+    }
+}
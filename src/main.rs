@@ -3,6 +3,7 @@
 mod config;
 mod entry;
 mod list_dir;
+mod locale;
 mod logger;
 mod ls_colors;
 mod os;
@@ -21,6 +22,8 @@ fn main() {
     log::debug!("{:?}", config);
     log::debug!("{:?}", path_args_vec);
 
+    output::print_delimited_header(&config);
+
     let num_path_args = path_args_vec.len();
 
     if num_path_args == 0 {
@@ -30,6 +33,10 @@ fn main() {
     } else {
         multiple_path_args(path_args_vec, &config);
     }
+
+    if config.dired {
+        output::print_dired_offsets(&config);
+    }
 }
 
 fn zero_path_args(config: &Config) {
@@ -40,7 +47,7 @@ fn zero_path_args(config: &Config) {
         if config.list_allocated_size {
             output::print_total(&entrybuf_vec, config);
         }
-        output::output(&mut entrybuf_vec, config);
+        output::output(&mut entrybuf_vec, None, config);
     } else if config.recursive {
         list_dir::recursive_list_dir(Path::new("."), config);
     } else {
@@ -70,7 +77,7 @@ fn one_path_arg(path: &Path, config: &Config) {
                 if config.list_allocated_size {
                     output::print_total(&entrybuf_vec, config);
                 }
-                output::output(&mut entrybuf_vec, config);
+                output::output(&mut entrybuf_vec, None, config);
             }
         }
         Err(err) => {
@@ -80,7 +87,11 @@ fn one_path_arg(path: &Path, config: &Config) {
 }
 
 fn multiple_path_args(path_args_vec: Vec<PathBuf>, config: &Config) {
-    let (list_non_dir_paths_vec, list_dir_paths_vec) = split_path_args_vec(path_args_vec, config);
+    let (list_non_dir_paths_vec, mut list_dir_paths_vec) =
+        split_path_args_vec(path_args_vec, config);
+    if config.sort_args {
+        list_dir_paths_vec = sort_dir_paths_vec(list_dir_paths_vec, config);
+    }
     let list_non_dir_paths_vec_is_empty = list_non_dir_paths_vec.is_empty();
 
     if !list_non_dir_paths_vec.is_empty() {
@@ -92,22 +103,22 @@ fn multiple_path_args(path_args_vec: Vec<PathBuf>, config: &Config) {
         if config.list_allocated_size {
             output::print_total(&entrybuf_vec, config);
         }
-        output::output(&mut entrybuf_vec, config);
+        output::output(&mut entrybuf_vec, None, config);
     }
 
     if !list_dir_paths_vec.is_empty() {
-        if list_non_dir_paths_vec_is_empty {
-            println!("{}:", &list_dir_paths_vec[0].display());
-        } else {
-            println!("\n{}:", &list_dir_paths_vec[0].display());
-        }
+        output::print_dir_header(
+            &list_dir_paths_vec[0],
+            list_non_dir_paths_vec_is_empty,
+            config,
+        );
 
         if config.recursive {
             list_dir::recursive_list_dir(&list_dir_paths_vec[0], config);
 
             let remainding_dir_paths_vec = &list_dir_paths_vec[1..];
             for path in remainding_dir_paths_vec {
-                println!("\n{}:", path.display());
+                output::print_dir_header(path, false, config);
                 list_dir::recursive_list_dir(path, config);
             }
         } else {
@@ -115,13 +126,28 @@ fn multiple_path_args(path_args_vec: Vec<PathBuf>, config: &Config) {
 
             let remainding_dir_paths_vec = &list_dir_paths_vec[1..];
             for path in remainding_dir_paths_vec {
-                println!("\n{}:", path.display());
+                output::print_dir_header(path, false, config);
                 let _ = list_dir::list_dir(path, config);
             }
         }
     }
 }
 
+/// Sorts directories passed on the command line like entries of a directory,
+/// as non-directories are sorted when they are listed.
+fn sort_dir_paths_vec(dir_paths_vec: Vec<PathBuf>, config: &Config) -> Vec<PathBuf> {
+    let mut entrybuf_vec: Vec<EntryBuf> = dir_paths_vec
+        .iter()
+        .map(|path| EntryBuf::from_cmdline_path(path, config))
+        .collect();
+    output::sort_entrybuf_vec(&mut entrybuf_vec, config);
+
+    entrybuf_vec
+        .iter()
+        .map(|entrybuf| entrybuf.path().to_path_buf())
+        .collect()
+}
+
 fn split_path_args_vec(
     path_args_vec: Vec<PathBuf>,
     config: &Config,
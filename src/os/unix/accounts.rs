@@ -1,5 +1,6 @@
 use std::sync::Mutex;
 
+use compact_str::{CompactString, ToCompactString};
 use once_cell::sync::Lazy;
 use user_utils::os::unix::{get_name_by_gid, get_name_by_uid};
 
@@ -68,6 +69,18 @@ pub fn get_groupname_cell_by_gid(gid: u32, config: &Config) -> GridCell {
     }
 }
 
+pub fn get_username_by_uid(uid: u32) -> Option<CompactString> {
+    get_name_by_uid(uid as libc::uid_t)
+        .ok()
+        .map(|name| name.to_string_lossy().to_compact_string())
+}
+
+pub fn get_groupname_by_gid(gid: u32) -> Option<CompactString> {
+    get_name_by_gid(gid as libc::gid_t)
+        .ok()
+        .map(|name| name.to_string_lossy().to_compact_string())
+}
+
 fn username_cell(uid: u32, config: &Config) -> GridCell {
     let owner_style = config.theme.owner_style();
     let raw_uid = uid as libc::uid_t;
@@ -2,18 +2,18 @@ mod accounts;
 mod mode;
 pub mod sys_prelude;
 
-pub use accounts::{get_groupname_cell_by_gid, get_username_cell_by_uid};
+pub use accounts::{
+    get_groupname_by_gid, get_groupname_cell_by_gid, get_username_by_uid, get_username_cell_by_uid,
+};
 pub use mode::{pwsh_mode_cell, rwx_mode_cell};
 
 use std::fs::Metadata;
 use std::os::unix::fs::MetadataExt;
 
-use nls_term_grid::Alignment;
-
 use sys_prelude::*;
 
 use crate::config::{AllocatedSizeBlocks, Config};
-use crate::output::{GridCell, GridCellExts};
+use crate::output::{Alignment, GridCell, GridCellExts};
 
 pub fn get_allocated_size(metadata: &Metadata, config: &Config) -> u64 {
     match config.allocated_size_blocks {
@@ -17,10 +17,9 @@ use std::path::Path;
 use std::ptr;
 
 use compact_str::CompactString;
-use nls_term_grid::Alignment;
 
-use crate::config::{AllocatedSizeBlocks, Config};
-use crate::output::{GridCell, GridCellExts};
+use crate::config::{AllocatedSizeBlocks, Config, GroupBy};
+use crate::output::{Alignment, GridCell, GridCellExts};
 
 use accounts::{get_groupname_by_psid, get_username_by_psid};
 use permissions::get_rwx_permissions;
@@ -43,14 +42,25 @@ impl WindowsMetadata {
         let wide_path = WideString::from_path(path);
         let mut windows_metadata = Self::default();
 
-        if config.output_format.is_long()
-            || config.sorting_order.is_size()
+        if config.output_format.lists_metadata()
+            || config.grid_columns.is_some()
+            || config
+                .sort_keys
+                .iter()
+                .any(|sort_key| sort_key.sorting_order.uses_file_standard_info())
             || config.list_allocated_size
         {
             windows_metadata.init_from_file_standard_info(&wide_path, path, follow_links);
         }
 
-        if config.output_format.is_long() {
+        if config.output_format.lists_metadata()
+            || config.grid_columns.is_some()
+            || config
+                .sort_keys
+                .iter()
+                .any(|sort_key| sort_key.sorting_order.uses_security_info())
+            || config.group_by == Some(GroupBy::Owner)
+        {
             windows_metadata.init_from_security_info(&wide_path, path, follow_links, config);
         }
 
@@ -119,6 +129,10 @@ impl WindowsMetadata {
         }
     }
 
+    pub fn nlink(&self) -> Option<u64> {
+        self.nlink
+    }
+
     pub fn nlink_cell(&self, config: &Config) -> GridCell {
         let nlink_style = config.theme.nlink_style();
         match &self.nlink {
@@ -131,6 +145,14 @@ impl WindowsMetadata {
         self.size
     }
 
+    pub fn owner_name(&self) -> Option<&str> {
+        if self.owner_string.is_empty() || self.owner_string == "?" {
+            None
+        } else {
+            Some(&self.owner_string)
+        }
+    }
+
     pub fn owner_cell(&self, config: &Config) -> GridCell {
         let owner_style = config.theme.owner_style();
         if self.owner_string == "?" {
@@ -140,6 +162,14 @@ impl WindowsMetadata {
         }
     }
 
+    pub fn group_name(&self) -> Option<&str> {
+        if self.group_string.is_empty() || self.group_string == "?" {
+            None
+        } else {
+            Some(&self.group_string)
+        }
+    }
+
     pub fn group_cell(&self, config: &Config) -> GridCell {
         let group_style = config.theme.group_style();
         if self.group_string == "?" {
@@ -1,139 +1,297 @@
-use nls_term_grid::*;
+use compact_str::CompactString;
 
 use crate::config::Config;
 use crate::entry::EntryBuf;
-use crate::output::{GridCell, GridCellExts};
+use crate::output::grid::{Direction, Grid};
+use crate::output::tree::prefixed_file_name_cell;
+use crate::output::{
+    print_bytes, print_str, write_rows_with_group_headings, Alignment, GridCell, GridCellExts,
+    GroupHeading, LongColumn,
+};
 
-pub fn vertical_format(entrybuf_vec: &[EntryBuf], config: &Config) {
-    multi_column_format(Direction::TopToBottom, entrybuf_vec, config)
+pub fn vertical_format(
+    entrybuf_vec: &[EntryBuf],
+    group_heading_vec: &[GroupHeading],
+    config: &Config,
+) {
+    multi_column_format(
+        Direction::TopToBottom,
+        entrybuf_vec,
+        group_heading_vec,
+        config,
+    )
+}
+
+pub fn across_format(
+    entrybuf_vec: &[EntryBuf],
+    group_heading_vec: &[GroupHeading],
+    config: &Config,
+) {
+    multi_column_format(
+        Direction::LeftToRight,
+        entrybuf_vec,
+        group_heading_vec,
+        config,
+    )
 }
 
-pub fn across_format(entrybuf_vec: &[EntryBuf], config: &Config) {
-    multi_column_format(Direction::LeftToRight, entrybuf_vec, config)
+/// Lists entries one per line, with each of `group_heading_vec` before its group.
+pub fn single_column_format(
+    entrybuf_vec: &[EntryBuf],
+    group_heading_vec: &[GroupHeading],
+    config: &Config,
+) {
+    write_single_column_format(entrybuf_vec, &[], group_heading_vec, config)
 }
 
-pub fn single_column_format(entrybuf_vec: &[EntryBuf], config: &Config) {
-    let num_columns: usize =
-        1 + (config.list_inode as usize) + (config.list_allocated_size as usize);
+/// Like [`single_column_format`], but each file name is prefixed with the prefix
+/// at the same index in `name_prefix_vec`.
+pub fn single_column_format_with_name_prefixes(
+    entrybuf_vec: &[EntryBuf],
+    name_prefix_vec: &[CompactString],
+    config: &Config,
+) {
+    write_single_column_format(entrybuf_vec, name_prefix_vec, &[], config)
+}
+
+fn write_single_column_format(
+    entrybuf_vec: &[EntryBuf],
+    name_prefix_vec: &[CompactString],
+    group_heading_vec: &[GroupHeading],
+    config: &Config,
+) {
+    let columns = config.grid_columns();
+    let num_columns = columns.len();
 
     if num_columns == 1 {
-        for entrybuf in entrybuf_vec {
-            let file_name_cell = entrybuf.file_name_cell(config);
-            println!("{}", file_name_cell.contents);
+        if config.header {
+            let header_cell = LongColumn::Name.header_cell(config);
+            print_str(&format!("{}\n", header_cell.contents));
         }
+        let mut buffer: Vec<u8> = Vec::with_capacity(entrybuf_vec.len() * 32);
+        let mut group_heading_iter = group_heading_vec.iter().peekable();
+        for (i, entrybuf) in entrybuf_vec.iter().enumerate() {
+            if let Some(group_heading) =
+                group_heading_iter.next_if(|group_heading| group_heading.entry_index == i)
+            {
+                group_heading.push_line(&mut buffer);
+            }
+            let file_name_cell = prefixed_file_name_cell(entrybuf, name_prefix_vec.get(i), config);
+            buffer.extend_from_slice(file_name_cell.bytes());
+            buffer.push(b'\n');
+        }
+        print_bytes(&buffer);
     } else {
-        let mut cells_vec: Vec<GridCell> = Vec::with_capacity(entrybuf_vec.len() * num_columns);
+        let mut cells_vec: Vec<GridCell> =
+            Vec::with_capacity((entrybuf_vec.len() + config.header as usize) * num_columns);
 
-        for entrybuf in entrybuf_vec {
-            if config.list_inode {
-                cells_vec.push(entrybuf.ino_cell(config));
+        if config.header {
+            for column in &columns {
+                cells_vec.push(column.header_cell(config));
             }
-            if config.list_allocated_size {
-                cells_vec.push(entrybuf.allocated_size_cell(config));
+        }
+        for (i, entrybuf) in entrybuf_vec.iter().enumerate() {
+            for column in &columns {
+                if *column == LongColumn::Name {
+                    cells_vec.push(prefixed_file_name_cell(
+                        entrybuf,
+                        name_prefix_vec.get(i),
+                        config,
+                    ));
+                } else {
+                    cells_vec.push(column.cell(entrybuf, config));
+                }
             }
-            cells_vec.push(entrybuf.file_name_cell(config));
         }
 
         let grid = Grid::new(" ", Direction::LeftToRight, &cells_vec);
-        print!("{}", grid.fit_into_columns(num_columns));
+        let mut buffer: Vec<u8> = Vec::with_capacity(cells_vec.len() * 16);
+        write_rows_with_group_headings(
+            &grid.fit_into_columns(num_columns),
+            &mut buffer,
+            config.header as usize,
+            group_heading_vec,
+            |_, _| {},
+        );
+        print_bytes(&buffer);
+    }
+}
+
+/// Lists file names separated by `, `, filling each line up to the line width.
+pub fn commas_format(entrybuf_vec: &[EntryBuf], config: &Config) {
+    let mut buffer: Vec<u8> = Vec::with_capacity(entrybuf_vec.len() * 32);
+    let mut line_width: usize = 0;
+
+    for (i, entrybuf) in entrybuf_vec.iter().enumerate() {
+        let cell = unpadded_file_name_cell(entrybuf, config);
+        if i != 0 {
+            // Like GNU ls, a line is wrapped before an entry followed by ", " would reach the width
+            if line_width + cell.width + 2 < config.width {
+                buffer.extend_from_slice(b", ");
+                line_width += 2;
+            } else {
+                buffer.extend_from_slice(b",\n");
+                line_width = 0;
+            }
+        }
+        buffer.extend_from_slice(cell.bytes());
+        line_width += cell.width;
+    }
+    buffer.push(b'\n');
+
+    print_bytes(&buffer);
+}
+
+/// Lists file names each terminated by a NUL byte instead of a newline.
+pub fn zero_format(entrybuf_vec: &[EntryBuf], config: &Config) {
+    let mut buffer: Vec<u8> = Vec::with_capacity(entrybuf_vec.len() * 32);
+    for entrybuf in entrybuf_vec {
+        buffer.extend_from_slice(unpadded_file_name_cell(entrybuf, config).bytes());
+        buffer.push(b'\0');
     }
+
+    print_bytes(&buffer);
 }
 
-fn multi_column_format(direction: Direction, entrybuf_vec: &[EntryBuf], config: &Config) {
-    use crate::utils::terminal_width;
+/// Returns a cell of the columns set by '--grid-columns', '-i' and '-s' separated by
+/// spaces without padding.
+fn unpadded_file_name_cell(entrybuf: &EntryBuf, config: &Config) -> GridCell {
+    let mut cell = GridCell::with_capacity(64);
+    for (i, column) in config.grid_columns().iter().enumerate() {
+        if i != 0 {
+            cell.push_char(' ');
+        }
+        cell.append(column.cell(entrybuf, config));
+    }
+
+    cell
+}
 
+fn multi_column_format(
+    direction: Direction,
+    entrybuf_vec: &[EntryBuf],
+    group_heading_vec: &[GroupHeading],
+    config: &Config,
+) {
     let mut cells_vec: Vec<GridCell> = Vec::with_capacity(entrybuf_vec.len());
 
-    if config.list_inode || config.list_allocated_size {
-        complex_multi_column_grid_init(&mut cells_vec, entrybuf_vec, config);
+    let columns = config.grid_columns();
+    if columns != [LongColumn::Name] {
+        complex_multi_column_grid_init(&mut cells_vec, entrybuf_vec, &columns, config);
     } else {
         for entrybuf in entrybuf_vec {
             cells_vec.push(entrybuf.file_name_cell(config));
         }
     }
 
-    let display_width = terminal_width().unwrap_or(80);
-    let grid = Grid::new("  ", direction, &cells_vec);
+    if group_heading_vec.is_empty() {
+        let grid = Grid::new("  ", direction, &cells_vec).with_tabsize(config.tabsize);
+        match grid.fit_into_width(config.width) {
+            Some(display) => print_bytes(&display.to_bytes()),
+            None => single_column_format(entrybuf_vec, &[], config),
+        }
+        return;
+    }
+
+    // Unlike long and single column output, the columns of '-C' and '-x' would run
+    // across group headings, so each group is laid out as its own grid
+    for (i, group_heading) in group_heading_vec.iter().enumerate() {
+        let start = group_heading.entry_index;
+        let end = group_heading_vec
+            .get(i + 1)
+            .map_or(entrybuf_vec.len(), |next_group_heading| {
+                next_group_heading.entry_index
+            });
 
-    match grid.fit_into_width(display_width) {
-        Some(display) => print!("{}", display),
-        None => single_column_format(entrybuf_vec, config),
+        let mut buffer: Vec<u8> = Vec::new();
+        group_heading.push_line(&mut buffer);
+        print_bytes(&buffer);
+
+        let grid = Grid::new("  ", direction, &cells_vec[start..end]).with_tabsize(config.tabsize);
+        match grid.fit_into_width(config.width) {
+            Some(display) => print_bytes(&display.to_bytes()),
+            None => single_column_format(&entrybuf_vec[start..end], &[], config),
+        }
     }
 }
 
+/// Initializes cells made of `columns` of each entry, where every column
+/// except the last is padded to its widest value and followed by a space.
 fn complex_multi_column_grid_init(
     cells_vec: &mut Vec<GridCell>,
     entrybuf_vec: &[EntryBuf],
+    columns: &[LongColumn],
     config: &Config,
 ) {
-    let entrybuf_count = entrybuf_vec.len();
-    let mut ino_cell_vec: Vec<GridCell> = Vec::with_capacity(entrybuf_count);
-    let mut max_ino_cell_width: usize = 0;
-    let mut allocated_size_cell_vec: Vec<GridCell> = Vec::with_capacity(entrybuf_count);
-    let mut max_allocated_size_cell_width: usize = 0;
+    let num_columns = columns.len();
+    let mut column_cells_vec: Vec<GridCell> = Vec::with_capacity(entrybuf_vec.len() * num_columns);
+    let mut max_column_widths: Vec<usize> = vec![0; num_columns];
 
     for entrybuf in entrybuf_vec {
-        if config.list_inode {
-            let ino_cell = entrybuf.ino_cell(config);
-            max_ino_cell_width = max_ino_cell_width.max(ino_cell.width);
-            ino_cell_vec.push(ino_cell);
-        }
-        if config.list_allocated_size {
-            let allocated_size_cell = entrybuf.allocated_size_cell(config);
-            max_allocated_size_cell_width =
-                max_allocated_size_cell_width.max(allocated_size_cell.width);
-            allocated_size_cell_vec.push(allocated_size_cell);
+        for (column, max_column_width) in columns.iter().zip(&mut max_column_widths) {
+            let column_cell = column.cell(entrybuf, config);
+            *max_column_width = (*max_column_width).max(column_cell.width);
+            column_cells_vec.push(column_cell);
         }
     }
 
-    for i in 0..entrybuf_count {
+    for entry_column_cells in column_cells_vec.chunks(num_columns) {
         let mut cell = GridCell::with_capacity(128);
-        if config.list_inode {
-            gridcell_append_contents_with_width_right_padded(
-                &mut cell,
-                &ino_cell_vec[i],
-                max_ino_cell_width,
-            );
-            cell.push_char(' ');
-        }
-        if config.list_allocated_size {
-            gridcell_append_contents_with_width_right_padded(
-                &mut cell,
-                &allocated_size_cell_vec[i],
-                max_allocated_size_cell_width,
-            );
-            cell.push_char(' ');
+        for (i, (column_cell, max_column_width)) in entry_column_cells
+            .iter()
+            .zip(&max_column_widths)
+            .enumerate()
+        {
+            if i == num_columns - 1 {
+                cell.append(column_cell.clone());
+            } else {
+                gridcell_append_contents_with_width_padded(
+                    &mut cell,
+                    column_cell,
+                    *max_column_width,
+                );
+                cell.push_char(' ');
+            }
         }
-        cell.append(entrybuf_vec[i].file_name_cell(config));
         cells_vec.push(cell);
     }
 }
 
-fn gridcell_append_contents_with_width_right_padded(
+/// Appends the contents of `other_cell` padded to `width` on the side set by its alignment.
+fn gridcell_append_contents_with_width_padded(
     cell: &mut GridCell,
     other_cell: &GridCell,
     width: usize,
 ) {
-    use std::fmt::Write;
+    let padding = " ".repeat(width.saturating_sub(other_cell.width));
 
-    let pad_width: usize = if width <= other_cell.width {
-        0
-    } else {
-        width - other_cell.width
-    };
-
-    // Check if pad width is 0
-    if pad_width == 0 {
-        // if pad width is 0, we do not need to do padding
-        cell.contents.push_str(&other_cell.contents);
-        cell.width += width;
+    if other_cell.alignment == Alignment::Right {
+        cell.push_str_with_width(&padding, padding.len());
+        cell.append(other_cell.clone());
     } else {
-        let _ = write!(
-            cell.contents,
-            "{}{}",
-            " ".repeat(pad_width),
-            other_cell.contents
+        cell.append(other_cell.clone());
+        cell.push_str_with_width(&padding, padding.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gridcell_append_contents_with_width_padded() {
+        let mut cell = GridCell::with_capacity(32);
+        gridcell_append_contents_with_width_padded(
+            &mut cell,
+            &GridCell::from_num_with_style(42, None),
+            4,
+        );
+        gridcell_append_contents_with_width_padded(
+            &mut cell,
+            &GridCell::from_ascii_str_with_style("root", None),
+            6,
         );
-        cell.width += width;
+        assert_eq!(&*cell.contents, "  42root  ");
+        assert_eq!(cell.width, 10);
     }
 }
@@ -0,0 +1,174 @@
+use crate::config::{Config, OutputFormat};
+use crate::entry::EntryBuf;
+use crate::output::{print_str, LongColumn};
+
+/// Prints the long format columns of entries as comma separated values.
+pub fn csv_format(entrybuf_vec: &[EntryBuf], config: &Config) {
+    delimited_format(entrybuf_vec, Delimiter::Comma, config)
+}
+
+/// Prints the long format columns of entries as tab separated values.
+pub fn tsv_format(entrybuf_vec: &[EntryBuf], config: &Config) {
+    delimited_format(entrybuf_vec, Delimiter::Tab, config)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Delimiter {
+    Comma,
+    Tab,
+}
+
+impl Delimiter {
+    fn of(output_format: &OutputFormat) -> Option<Self> {
+        match output_format {
+            OutputFormat::Csv => Some(Self::Comma),
+            OutputFormat::Tsv => Some(Self::Tab),
+            _ => None,
+        }
+    }
+
+    fn as_char(&self) -> char {
+        match self {
+            Self::Comma => ',',
+            Self::Tab => '\t',
+        }
+    }
+}
+
+/// Prints the header row of CSV and TSV output, once before any entry is listed.
+pub fn print_delimited_header(config: &Config) {
+    let delimiter = match Delimiter::of(&config.output_format) {
+        Some(delimiter) => delimiter,
+        None => return,
+    };
+
+    let mut buffer = String::with_capacity(96);
+    let mut row = Row::new(&mut buffer, delimiter);
+    for column in &config.long_columns() {
+        row.push_field(column.header_name(config));
+    }
+    // The path tells apart entries of different directories listed by '-R'
+    row.push_field("path");
+    row.end();
+
+    print_str(&buffer);
+}
+
+fn delimited_format(entrybuf_vec: &[EntryBuf], delimiter: Delimiter, config: &Config) {
+    let mut buffer = String::with_capacity(entrybuf_vec.len() * 128);
+
+    let columns = config.long_columns();
+    for entrybuf in entrybuf_vec {
+        let mut row = Row::new(&mut buffer, delimiter);
+        for column in &columns {
+            if *column == LongColumn::Name {
+                row.push_field(entrybuf.file_name());
+            } else {
+                row.push_field(&column.cell(entrybuf, config).contents);
+            }
+        }
+        row.push_field(&entrybuf.path().to_string_lossy());
+        row.end();
+    }
+
+    print_str(&buffer);
+}
+
+/// A row of delimiter separated fields being written into a buffer.
+struct Row<'a> {
+    buffer: &'a mut String,
+    delimiter: Delimiter,
+    is_empty: bool,
+}
+
+impl<'a> Row<'a> {
+    fn new(buffer: &'a mut String, delimiter: Delimiter) -> Self {
+        Self {
+            buffer,
+            delimiter,
+            is_empty: true,
+        }
+    }
+
+    fn push_field(&mut self, value: &str) {
+        if !self.is_empty {
+            self.buffer.push(self.delimiter.as_char());
+        }
+        self.is_empty = false;
+
+        match self.delimiter {
+            Delimiter::Comma => push_csv_field(self.buffer, value),
+            Delimiter::Tab => push_tsv_field(self.buffer, value),
+        }
+    }
+
+    fn end(self) {
+        self.buffer.push('\n');
+    }
+}
+
+/// Pushes a field quoted as described in RFC 4180 if it contains
+/// a comma, double quote or line break.
+fn push_csv_field(buffer: &mut String, value: &str) {
+    if value.contains([',', '"', '\r', '\n']) {
+        buffer.push('"');
+        for ch in value.chars() {
+            if ch == '"' {
+                buffer.push('"');
+            }
+            buffer.push(ch);
+        }
+        buffer.push('"');
+    } else {
+        buffer.push_str(value);
+    }
+}
+
+/// Pushes a field with tabs, line breaks and backslashes escaped,
+/// as TSV has no quoting mechanism.
+fn push_tsv_field(buffer: &mut String, value: &str) {
+    for ch in value.chars() {
+        match ch {
+            '\t' => buffer.push_str("\\t"),
+            '\n' => buffer.push_str("\\n"),
+            '\r' => buffer.push_str("\\r"),
+            '\\' => buffer.push_str("\\\\"),
+            ch => buffer.push(ch),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_csv_field() {
+        let mut buffer = String::new();
+        push_csv_field(&mut buffer, "main.rs");
+        assert_eq!(buffer, "main.rs");
+
+        let mut buffer = String::new();
+        push_csv_field(&mut buffer, "a, \"b\"\nc");
+        assert_eq!(buffer, "\"a, \"\"b\"\"\nc\"");
+    }
+
+    #[test]
+    fn test_push_tsv_field() {
+        let mut buffer = String::new();
+        push_tsv_field(&mut buffer, "a\tb\\c\nd");
+        assert_eq!(buffer, "a\\tb\\\\c\\nd");
+    }
+
+    #[test]
+    fn test_row() {
+        let mut buffer = String::new();
+        let mut row = Row::new(&mut buffer, Delimiter::Comma);
+        row.push_field("-rw-r--r--");
+        row.push_field("1");
+        row.push_field("a,b");
+        row.end();
+
+        assert_eq!(buffer, "-rw-r--r--,1,\"a,b\"\n");
+    }
+}
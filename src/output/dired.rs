@@ -0,0 +1,87 @@
+use std::sync::Mutex;
+
+use crate::config::Config;
+use crate::output::print_str;
+
+/// The indentation of every line listed in dired mode.
+pub const DIRED_INDENT: &str = "  ";
+
+/// Byte offsets in the output of names listed in dired mode, as read by Emacs' dired.
+struct DiredOffsets {
+    /// Number of bytes printed to stdout so far.
+    printed_len: usize,
+    /// `(start, end)` of each file name.
+    file_name_vec: Vec<(usize, usize)>,
+    /// `(start, end)` of the path of each directory header.
+    dir_name_vec: Vec<(usize, usize)>,
+}
+
+static DIRED_OFFSETS: Mutex<DiredOffsets> = Mutex::new(DiredOffsets {
+    printed_len: 0,
+    file_name_vec: Vec::new(),
+    dir_name_vec: Vec::new(),
+});
+
+/// Counts bytes printed to stdout, which file name offsets are relative to.
+///
+/// Only output printed while listing in dired mode is counted.
+pub fn add_printed_len(len: usize) {
+    DIRED_OFFSETS.lock().unwrap().printed_len += len;
+}
+
+/// Records a file name that starts `start` bytes after what has been printed so far.
+pub fn push_file_name(start: usize, len: usize) {
+    let mut dired_offsets = DIRED_OFFSETS.lock().unwrap();
+    let start = dired_offsets.printed_len + start;
+    dired_offsets.file_name_vec.push((start, start + len));
+}
+
+/// Records the path of a directory header that starts `start` bytes after
+/// what has been printed so far.
+pub fn push_dir_name(start: usize, len: usize) {
+    let mut dired_offsets = DIRED_OFFSETS.lock().unwrap();
+    let start = dired_offsets.printed_len + start;
+    dired_offsets.dir_name_vec.push((start, start + len));
+}
+
+/// Prints the `//DIRED//`, `//SUBDIRED//` and `//DIRED-OPTIONS//` lines after the listing.
+pub fn print_dired_offsets(config: &Config) {
+    let dired_offsets = DIRED_OFFSETS.lock().unwrap();
+    let mut buffer = String::with_capacity(64 + dired_offsets.file_name_vec.len() * 16);
+
+    buffer.push_str("//DIRED//");
+    push_offsets(&mut buffer, &dired_offsets.file_name_vec);
+    buffer.push('\n');
+    if !dired_offsets.dir_name_vec.is_empty() {
+        buffer.push_str("//SUBDIRED//");
+        push_offsets(&mut buffer, &dired_offsets.dir_name_vec);
+        buffer.push('\n');
+    }
+    buffer.push_str("//DIRED-OPTIONS// --quoting-style=");
+    buffer.push_str(config.quoting_style.word());
+    buffer.push('\n');
+
+    print_str(&buffer);
+}
+
+fn push_offsets(buffer: &mut String, offset_vec: &[(usize, usize)]) {
+    let mut itoa_buffer = itoa::Buffer::new();
+    for (start, end) in offset_vec {
+        buffer.push(' ');
+        buffer.push_str(itoa_buffer.format(*start));
+        buffer.push(' ');
+        buffer.push_str(itoa_buffer.format(*end));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_offsets() {
+        let mut buffer = String::from("//DIRED//");
+        push_offsets(&mut buffer, &[(52, 56), (110, 117)]);
+        assert_eq!(buffer, "//DIRED// 52 56 110 117");
+    }
+}
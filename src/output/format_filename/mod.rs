@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod test;
 
+use std::ffi::OsStr;
 #[cfg(unix)]
 use std::fs::FileType;
 use std::fs::Metadata;
@@ -8,37 +9,35 @@ use std::fs::Metadata;
 use std::os::unix::fs::{FileTypeExt, MetadataExt};
 use std::path::Path;
 
-use compact_str::CompactString;
-use nls_term_grid::Alignment;
-
 use crate::config::{Config, IndicatorStyle};
 use crate::ls_colors::get_file_extension;
 #[cfg(unix)]
 use crate::os::unix::sys_prelude::*;
-use crate::output::{GridCell, GridCellExts};
+use crate::output::hyperlink::{hyperlink_start, HYPERLINK_END};
+use crate::output::{quote_file_name, GridCell, GridCellExts};
 #[cfg(unix)]
 use crate::utils::HasMaskSetExt;
 
 pub fn format_filename(
     path: &Path,
-    file_name: &str,
+    file_name: &OsStr,
     metadata: &Metadata,
     config: &Config,
 ) -> GridCell {
     let file_type = metadata.file_type();
 
     if file_type.is_file() {
-        internal_format_regular_file(file_name, metadata, config)
+        internal_format_regular_file(path, file_name, metadata, config)
     } else if file_type.is_dir() {
-        internal_format_dir(file_name, metadata, config)
+        internal_format_dir(path, file_name, metadata, config)
     } else if file_type.is_symlink() {
         internal_format_symlink(path, file_name, config)
     } else {
         cfg_if::cfg_if! {
             if #[cfg(unix)] {
-                internal_format_unix_file_type_exts(file_name, file_type, config)
+                internal_format_unix_file_type_exts(path, file_name, file_type, config)
             } else {
-                GridCell::from_str_with_style(file_name, None)
+                create_filename_cell(path, file_name, None, None, config)
             }
         }
     }
@@ -46,7 +45,8 @@ pub fn format_filename(
 
 #[cfg(unix)]
 fn internal_format_unix_file_type_exts(
-    file_name: &str,
+    path: &Path,
+    file_name: &OsStr,
     file_type: FileType,
     config: &Config,
 ) -> GridCell {
@@ -55,57 +55,89 @@ fn internal_format_unix_file_type_exts(
     let icons = &config.icons;
 
     if file_type.is_block_device() {
-        return create_filename_cell(
+        create_filename_cell(
+            path,
             file_name,
             ls_colors.block_device_style(),
             icons.block_device_icon(),
-        );
+            config,
+        )
     } else if file_type.is_char_device() {
-        return create_filename_cell(
+        create_filename_cell(
+            path,
             file_name,
             ls_colors.char_device_style(),
             icons.char_device_icon(),
-        );
+            config,
+        )
     } else if file_type.is_fifo() {
-        let mut filename_cell =
-            create_filename_cell(file_name, ls_colors.fifo_style(), icons.fifo_icon());
+        let mut filename_cell = create_filename_cell(
+            path,
+            file_name,
+            ls_colors.fifo_style(),
+            icons.fifo_icon(),
+            config,
+        );
         if indicator_style.others() {
             filename_cell.push_char(IndicatorStyle::FIFO);
         }
-        return filename_cell;
+        filename_cell
     } else if file_type.is_socket() {
-        let mut filename_cell =
-            create_filename_cell(file_name, ls_colors.socket_style(), icons.socket_icon());
+        let mut filename_cell = create_filename_cell(
+            path,
+            file_name,
+            ls_colors.socket_style(),
+            icons.socket_icon(),
+            config,
+        );
         if indicator_style.others() {
             filename_cell.push_char(IndicatorStyle::SOCKET);
         }
-        return filename_cell;
+        filename_cell
     } else {
-        return GridCell::from_str_with_style(file_name, None);
+        create_filename_cell(path, file_name, None, None, config)
     }
 }
 
 #[cfg(unix)]
-fn internal_format_regular_file(file_name: &str, metadata: &Metadata, config: &Config) -> GridCell {
+fn internal_format_regular_file(
+    path: &Path,
+    file_name: &OsStr,
+    metadata: &Metadata,
+    config: &Config,
+) -> GridCell {
     const EXEC_MASK: u32 = c::S_IXUSR | c::S_IXGRP | c::S_IXOTH;
     let indicator_style = config.indicator_style;
     let ls_colors = &config.ls_colors;
     let st_mode = metadata.mode();
-    let extension = get_file_extension(file_name);
-    let icon = config.icons.file_icon(file_name, &extension);
+    let file_name_str = file_name.to_string_lossy();
+    let extension = get_file_extension(&file_name_str);
+    let icon = config.icons.file_icon(&file_name_str, &extension);
 
     let mut filename_cell = if st_mode.has_mask_set(c::S_ISUID) {
-        create_filename_cell(file_name, ls_colors.setuid_style(), icon)
+        create_filename_cell(path, file_name, ls_colors.setuid_style(), icon, config)
     } else if st_mode.has_mask_set(c::S_ISGID) {
-        create_filename_cell(file_name, ls_colors.setgid_style(), icon)
+        create_filename_cell(path, file_name, ls_colors.setgid_style(), icon, config)
     } else if st_mode.has_bit_in_mask_set(EXEC_MASK) {
-        create_filename_cell(file_name, ls_colors.exec_style(), icon)
+        create_filename_cell(path, file_name, ls_colors.exec_style(), icon, config)
     } else if metadata.nlink() > 1 {
-        create_filename_cell(file_name, ls_colors.multiple_hard_links_style(), icon)
+        create_filename_cell(
+            path,
+            file_name,
+            ls_colors.multiple_hard_links_style(),
+            icon,
+            config,
+        )
     } else if extension.is_empty() {
-        create_filename_cell(file_name, ls_colors.file_style(), icon)
+        create_filename_cell(path, file_name, ls_colors.file_style(), icon, config)
     } else {
-        create_filename_cell(file_name, ls_colors.extension_style(&extension), icon)
+        create_filename_cell(
+            path,
+            file_name,
+            ls_colors.extension_style(&extension),
+            icon,
+            config,
+        )
     };
 
     if indicator_style.others() && st_mode.has_bit_in_mask_set(EXEC_MASK) {
@@ -117,35 +149,49 @@ fn internal_format_regular_file(file_name: &str, metadata: &Metadata, config: &C
 
 #[cfg(not(unix))]
 fn internal_format_regular_file(
-    file_name: &str,
+    path: &Path,
+    file_name: &OsStr,
     _metadata: &Metadata,
     config: &Config,
 ) -> GridCell {
     let indicator_style = config.indicator_style;
     let ls_colors = &config.ls_colors;
-    let extension = get_file_extension(file_name);
-    let icon = config.icons.file_icon(file_name, &extension);
+    let file_name_str = file_name.to_string_lossy();
+    let extension = get_file_extension(&file_name_str);
+    let icon = config.icons.file_icon(&file_name_str, &extension);
 
     if extension.is_empty() {
-        return create_filename_cell(file_name, ls_colors.file_style(), icon);
+        return create_filename_cell(path, file_name, ls_colors.file_style(), icon, config);
     } else {
         #[cfg(windows)]
         if ["exe", "bat", "cmd"].contains(&extension.as_str()) {
-            let mut filename_cell = create_filename_cell(file_name, ls_colors.exec_style(), icon);
+            let mut filename_cell =
+                create_filename_cell(path, file_name, ls_colors.exec_style(), icon, config);
             if indicator_style.others() {
                 filename_cell.push_char(IndicatorStyle::EXEC);
             }
             return filename_cell;
         }
 
-        return create_filename_cell(file_name, ls_colors.extension_style(&extension), icon);
+        return create_filename_cell(
+            path,
+            file_name,
+            ls_colors.extension_style(&extension),
+            icon,
+            config,
+        );
     }
 }
 
-fn internal_format_dir(file_name: &str, _metadata: &Metadata, config: &Config) -> GridCell {
+fn internal_format_dir(
+    path: &Path,
+    file_name: &OsStr,
+    _metadata: &Metadata,
+    config: &Config,
+) -> GridCell {
     let indicator_style = config.indicator_style;
     let ls_colors = &config.ls_colors;
-    let icon = config.icons.dir_icon(file_name);
+    let icon = config.icons.dir_icon(&file_name.to_string_lossy());
 
     cfg_if::cfg_if! {
         if #[cfg(unix)] {
@@ -154,16 +200,14 @@ fn internal_format_dir(file_name: &str, _metadata: &Metadata, config: &Config) -
                 st_mode.has_mask_set(c::S_ISVTX),
                 st_mode.has_mask_set(c::S_IWOTH),
             ) {
-                (false, false) => create_filename_cell(file_name, ls_colors.dir_style(), icon),
-                (true, false) => create_filename_cell(file_name, ls_colors.dir_sticky_style(), icon),
-                (false, true) => create_filename_cell(file_name, ls_colors.dir_other_writable_style(), icon),
-                _ => create_filename_cell(
-                    file_name,
-                    ls_colors.dir_sticky_and_other_writable_style(), icon
-                ),
+                (false, false) => create_filename_cell(path, file_name, ls_colors.dir_style(), icon, config),
+                (true, false) => create_filename_cell(path, file_name, ls_colors.dir_sticky_style(), icon, config),
+                (false, true) => create_filename_cell(path, file_name, ls_colors.dir_other_writable_style(), icon, config),
+                _ => create_filename_cell(path, file_name,
+                    ls_colors.dir_sticky_and_other_writable_style(), icon, config),
             };
         } else {
-            let mut filename_cell = create_filename_cell(file_name, ls_colors.dir_style(), icon);
+            let mut filename_cell = create_filename_cell(path, file_name, ls_colors.dir_style(), icon, config);
         }
     }
 
@@ -174,12 +218,13 @@ fn internal_format_dir(file_name: &str, _metadata: &Metadata, config: &Config) -
     filename_cell
 }
 
-fn internal_format_symlink(path: &Path, file_name: &str, config: &Config) -> GridCell {
+fn internal_format_symlink(path: &Path, file_name: &OsStr, config: &Config) -> GridCell {
     let indicator_style = config.indicator_style;
     let ls_colors = &config.ls_colors;
     let icon = config.icons.symlink_icon();
 
-    let mut filename_cell = create_filename_cell(file_name, ls_colors.symlink_style(), icon);
+    let mut filename_cell =
+        create_filename_cell(path, file_name, ls_colors.symlink_style(), icon, config);
 
     if indicator_style.others() && !config.output_format.is_long() {
         filename_cell.push_char(IndicatorStyle::SYMLINK);
@@ -191,16 +236,20 @@ fn internal_format_symlink(path: &Path, file_name: &str, config: &Config) -> Gri
         match path.read_link() {
             Ok(target_name) => match path.metadata() {
                 Ok(target_metadata) => {
-                    let target_name_str: &str = &target_name.to_string_lossy();
+                    // Relative targets are relative to the directory of the symlink
+                    let target_path = match path.parent() {
+                        Some(parent_path) => parent_path.join(&target_name),
+                        None => target_name.clone(),
+                    };
                     filename_cell.append(format_filename(
-                        path,
-                        target_name_str,
+                        &target_path,
+                        target_name.as_os_str(),
                         &target_metadata,
                         config,
                     ));
                 }
                 Err(err) => {
-                    filename_cell.push_str(&target_name.to_string_lossy());
+                    filename_cell.append(quote_file_name(target_name.as_os_str(), config));
                     eprintln!(
                         "nls: unable to get link metadata of '{}': {}",
                         path.display(),
@@ -218,38 +267,40 @@ fn internal_format_symlink(path: &Path, file_name: &str, config: &Config) -> Gri
     filename_cell
 }
 
+/// Creates a cell of a file name quoted as set by '--quoting-style' and linked to `path`
+/// if '--hyperlink' is set, where the width is that of the quoted file name.
 fn create_filename_cell(
-    file_name: &str,
+    path: &Path,
+    file_name: &OsStr,
     ansi_style_str: Option<&str>,
     icon: Option<char>,
+    config: &Config,
 ) -> GridCell {
-    use unicode_width::UnicodeWidthStr;
-
-    let mut contents = CompactString::default();
-    let mut width: usize = 0;
+    let mut cell = GridCell::with_capacity(file_name.len() + 16);
 
     if let Some(ansi_style_str) = ansi_style_str {
-        contents.push_str("\x1b[");
-        contents.push_str(ansi_style_str);
-        contents.push('m');
+        cell.push_str_with_width("\x1b[", 0);
+        cell.push_str_with_width(ansi_style_str, 0);
+        cell.push_str_with_width("m", 0);
     }
 
     if let Some(icon) = icon {
-        contents.push(icon);
-        contents.push(' ');
-        width += 2;
+        cell.push_char(icon);
+        cell.push_char(' ');
     }
 
-    contents.push_str(file_name);
-    width += UnicodeWidthStr::width(file_name);
+    // The hyperlink escape sequences take up no width
+    if config.hyperlink {
+        cell.push_str_with_width(&hyperlink_start(path), 0);
+        cell.append(quote_file_name(file_name, config));
+        cell.push_str_with_width(HYPERLINK_END, 0);
+    } else {
+        cell.append(quote_file_name(file_name, config));
+    }
 
     if ansi_style_str.is_some() {
-        contents.push_str("\x1b[0m");
+        cell.push_str_with_width("\x1b[0m", 0);
     }
 
-    GridCell {
-        contents: contents,
-        width: width,
-        alignment: Alignment::Left,
-    }
+    cell
 }
@@ -2,25 +2,32 @@
 
 use super::*;
 
-#[cfg(unix)]
+use std::ffi::OsStr;
 use std::path::Path;
 
 use compact_str::{format_compact, CompactString};
 use unicode_width::UnicodeWidthStr;
 
 use crate::ls_colors::LsColors;
-use crate::output::GridCell;
+use crate::output::{Alignment, GridCell};
 use crate::theme::IconTheme;
 
 #[test]
 fn test_create_filename_cell() {
     let file_name = "dir1";
 
-    let cell = create_filename_cell(file_name, None, None);
+    let cell = create_filename_cell(
+        Path::new(file_name),
+        OsStr::new(file_name),
+        None,
+        None,
+        &Config::default(),
+    );
     let correct_cell = GridCell {
         contents: CompactString::from(file_name),
         width: 4,
         alignment: Alignment::Left,
+        raw_contents: None,
     };
 
     assert_eq!(cell, correct_cell);
@@ -33,7 +40,13 @@ fn test_create_filename_cell_with_color() {
     let ls_colors = &config.ls_colors;
     let file_name = "dir1";
 
-    let cell = create_filename_cell(file_name, ls_colors.dir_style(), None);
+    let cell = create_filename_cell(
+        Path::new(file_name),
+        OsStr::new(file_name),
+        ls_colors.dir_style(),
+        None,
+        &config,
+    );
     let correct_cell = GridCell {
         contents: format_compact!(
             "\x1b[{}m{}\x1b[0m",
@@ -42,6 +55,7 @@ fn test_create_filename_cell_with_color() {
         ),
         width: 4,
         alignment: Alignment::Left,
+        raw_contents: None,
     };
 
     assert_eq!(cell, correct_cell);
@@ -54,16 +68,44 @@ fn test_create_filename_cell_with_icon() {
     let icons = &config.icons;
     let file_name = "dir1";
 
-    let cell = create_filename_cell(file_name, None, icons.dir_icon(file_name));
+    let cell = create_filename_cell(
+        Path::new(file_name),
+        OsStr::new(file_name),
+        None,
+        icons.dir_icon(file_name),
+        &config,
+    );
     let correct_cell = GridCell {
         contents: format_compact!("{} {}", icons.dir_icon(file_name).unwrap(), file_name),
         width: 6,
         alignment: Alignment::Left,
+        raw_contents: None,
     };
 
     assert_eq!(cell, correct_cell);
 }
 
+#[cfg(unix)]
+#[test]
+fn test_create_filename_cell_with_hyperlink() {
+    let config = Config {
+        hyperlink: true,
+        ..Default::default()
+    };
+    let file_name = "dir1";
+
+    let cell = create_filename_cell(
+        Path::new("/tmp/dir1"),
+        OsStr::new(file_name),
+        None,
+        None,
+        &config,
+    );
+    assert!(cell.contents.starts_with("\x1b]8;;file://"));
+    assert!(cell.contents.ends_with("/tmp/dir1\x07dir1\x1b]8;;\x07"));
+    assert_eq!(cell.width, 4);
+}
+
 #[test]
 fn test_create_filename_cell_with_icon_and_color() {
     let mut config = Config::default();
@@ -73,7 +115,13 @@ fn test_create_filename_cell_with_icon_and_color() {
     let icons = &config.icons;
     let file_name = "dir1";
 
-    let cell = create_filename_cell(file_name, ls_colors.dir_style(), icons.dir_icon(file_name));
+    let cell = create_filename_cell(
+        Path::new(file_name),
+        OsStr::new(file_name),
+        ls_colors.dir_style(),
+        icons.dir_icon(file_name),
+        &config,
+    );
     let correct_cell = GridCell {
         contents: format_compact!(
             "\x1b[{}m{} {}\x1b[0m",
@@ -83,6 +131,7 @@ fn test_create_filename_cell_with_icon_and_color() {
         ),
         width: 6,
         alignment: Alignment::Left,
+        raw_contents: None,
     };
 
     assert_eq!(cell, correct_cell);
@@ -1073,13 +1122,17 @@ fn internal_test_format_filename_symlink_long_format(
 
     let filename_cell = format_filename(
         &symlink_path,
-        &symlink_path_string,
+        OsStr::new(&symlink_path_string),
         &symlink_metadata,
         &config,
     );
     let mut correct_filename_cell = GridCell::from_str_with_style(&symlink_path_string, None);
-    let target_name_cell =
-        format_filename(&target_path, &target_path_string, &target_metadata, &config);
+    let target_name_cell = format_filename(
+        &target_path,
+        OsStr::new(&target_path_string),
+        &target_metadata,
+        &config,
+    );
     correct_filename_cell.push_str(" -> ");
     correct_filename_cell.append(target_name_cell);
 
@@ -1088,7 +1141,7 @@ fn internal_test_format_filename_symlink_long_format(
     config.ls_colors = LsColors::with_colors();
     let filename_cell_with_color = format_filename(
         &symlink_path,
-        &symlink_path_string,
+        OsStr::new(&symlink_path_string),
         &symlink_metadata,
         &config,
     );
@@ -1099,8 +1152,12 @@ fn internal_test_format_filename_symlink_long_format(
         ),
         None => GridCell::from_str_with_style(&symlink_path_string, None),
     };
-    let target_name_cell =
-        format_filename(&target_path, &target_path_string, &target_metadata, &config);
+    let target_name_cell = format_filename(
+        &target_path,
+        OsStr::new(&target_path_string),
+        &target_metadata,
+        &config,
+    );
     correct_filename_cell_with_color.push_str(" -> ");
     correct_filename_cell_with_color.append(target_name_cell);
 
@@ -1214,7 +1271,7 @@ fn internal_test_format_filename_common(
     let mut config = Config::default();
     config.indicator_style = indicator_style;
 
-    let filename_cell = format_filename(path, &path_string, &metadata, &config);
+    let filename_cell = format_filename(path, OsStr::new(&path_string), &metadata, &config);
     let mut correct_filename_cell = GridCell::from_str_with_style(&path_string, None);
     if correct_filename_has_indicator {
         correct_filename_cell.push_char(indicator_symbol);
@@ -1222,7 +1279,8 @@ fn internal_test_format_filename_common(
     assert_eq!(filename_cell, correct_filename_cell);
 
     config.ls_colors = LsColors::with_colors();
-    let filename_cell_with_color = format_filename(path, &path_string, &metadata, &config);
+    let filename_cell_with_color =
+        format_filename(path, OsStr::new(&path_string), &metadata, &config);
     let mut correct_filename_cell_with_color = match &ansi_style_str {
         Some(ansi_style_str) => GridCell::from_str_with_style(
             &format_compact!("\x1b[{}m{}\x1b[0m", ansi_style_str, path_string),
@@ -1,13 +1,20 @@
-use nls_term_grid::Alignment;
-
 use crate::config::{Config, SizeFormat};
-use crate::output::{GridCell, GridCellExts};
+use crate::output::{Alignment, GridCell, GridCellExts};
 
 pub fn format_size(size: u64, config: &Config) -> GridCell {
+    format_size_with_size_format(size, &config.size_format, config)
+}
+
+/// Like [`format_size`], but uses `size_format` instead of the configured size format.
+pub fn format_size_with_size_format(
+    size: u64,
+    size_format: &SizeFormat,
+    config: &Config,
+) -> GridCell {
     let size_style = config.theme.size_style();
     let mut buffer = size_fmt::Buffer::new();
 
-    let size_str = match config.size_format {
+    let size_str = match size_format {
         SizeFormat::Raw => buffer.raw_fmt(size),
         SizeFormat::HumanReadable => buffer.human_fmt(size),
         SizeFormat::Si => buffer.si_fmt(size),
@@ -138,6 +145,7 @@ mod tests {
             contents: CompactString::new(raw_str),
             width: raw_str.len(),
             alignment: Alignment::Right,
+            raw_contents: None,
         };
         assert_eq!(format_size(size, &config), correct_cell);
 
@@ -146,6 +154,7 @@ mod tests {
             contents: CompactString::new(human_readable_str),
             width: human_readable_str.len(),
             alignment: Alignment::Right,
+            raw_contents: None,
         };
         assert_eq!(format_size(size, &config), correct_cell);
 
@@ -154,6 +163,7 @@ mod tests {
             contents: CompactString::new(si_str),
             width: si_str.len(),
             alignment: Alignment::Right,
+            raw_contents: None,
         };
         assert_eq!(format_size(size, &config), correct_cell);
 
@@ -162,6 +172,7 @@ mod tests {
             contents: CompactString::new(iec_str),
             width: iec_str.len(),
             alignment: Alignment::Right,
+            raw_contents: None,
         };
         assert_eq!(format_size(size, &config), correct_cell);
 
@@ -173,6 +184,7 @@ mod tests {
             contents: format_compact!("\x1b[{}m{}\x1b[0m", size_style, raw_str),
             width: raw_str.len(),
             alignment: Alignment::Right,
+            raw_contents: None,
         };
         assert_eq!(format_size(size, &config), correct_cell);
 
@@ -181,6 +193,7 @@ mod tests {
             contents: format_compact!("\x1b[{}m{}\x1b[0m", size_style, human_readable_str),
             width: human_readable_str.len(),
             alignment: Alignment::Right,
+            raw_contents: None,
         };
         assert_eq!(format_size(size, &config), correct_cell);
 
@@ -189,6 +202,7 @@ mod tests {
             contents: format_compact!("\x1b[{}m{}\x1b[0m", size_style, si_str),
             width: si_str.len(),
             alignment: Alignment::Right,
+            raw_contents: None,
         };
         assert_eq!(format_size(size, &config), correct_cell);
 
@@ -197,6 +211,7 @@ mod tests {
             contents: format_compact!("\x1b[{}m{}\x1b[0m", size_style, iec_str),
             width: iec_str.len(),
             alignment: Alignment::Right,
+            raw_contents: None,
         };
         assert_eq!(format_size(size, &config), correct_cell);
     }
@@ -0,0 +1,391 @@
+use anyhow::anyhow;
+use compact_str::CompactString;
+
+use crate::config::{Config, SizeFormat, TimestampUsed};
+use crate::entry::EntryBuf;
+use crate::output::format_timestamp::{
+    format_timestamp_with_strftime_format, is_valid_strftime_format,
+};
+use crate::output::{
+    format_size_with_size_format, format_timestamp, print_bytes, quote_file_name, Alignment,
+    GridCell, GridCellExts,
+};
+
+/// A parsed user-defined format string such as `'{mode} {size:h:>} {mtime:%F} {name}'`.
+///
+/// A field is written as `{NAME[:MODIFIER][:PADDING]}`, while `{{` and `}}`
+/// are written as literal braces.
+/// * `MODIFIER` is a size format (`raw`, `h`, `si`, `iec`) for `size`
+///   or a strftime format for timestamps.
+/// * `PADDING` is `<` to left align or `>` to right align, optionally followed by a width.
+///   Without a width, the field is padded to the widest value in the listing.
+///
+/// `PADDING` is always the last `:`-separated part, so a strftime format whose last part
+/// reads as a padding, such as `%H:<`, is followed by an empty padding: `{mtime:%H:<:}`.
+#[derive(Debug, Default)]
+pub struct FormatString {
+    tokens: Vec<FormatToken>,
+}
+
+#[derive(Debug)]
+enum FormatToken {
+    Literal(CompactString),
+    Field(FormatField),
+}
+
+#[derive(Debug)]
+struct FormatField {
+    kind: FieldKind,
+    padding: Padding,
+}
+
+#[derive(Debug)]
+enum FieldKind {
+    Name,
+    Path,
+    Inode,
+    Blocks,
+    Mode,
+    Nlink,
+    Owner,
+    Group,
+    Size(Option<SizeFormat>),
+    /// A timestamp, where `None` is the timestamp set with '--time', '-c' or '-u'.
+    Timestamp(Option<TimestampUsed>, Option<CompactString>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Padding {
+    None,
+    Left(Option<usize>),
+    Right(Option<usize>),
+}
+
+impl FormatString {
+    pub fn parse(format: &str) -> anyhow::Result<Self> {
+        let mut tokens: Vec<FormatToken> = Vec::with_capacity(8);
+        let mut literal = CompactString::default();
+        let mut chars = format.chars();
+
+        while let Some(ch) = chars.next() {
+            match ch {
+                '{' => {
+                    if chars.as_str().starts_with('{') {
+                        chars.next();
+                        literal.push('{');
+                        continue;
+                    }
+
+                    let rest = chars.as_str();
+                    let end = rest.find('}').ok_or_else(|| {
+                        anyhow!("unterminated field '{{{}' in format string", rest)
+                    })?;
+                    let field = FormatField::parse(&rest[..end])?;
+                    chars = rest[end + 1..].chars();
+
+                    if !literal.is_empty() {
+                        tokens.push(FormatToken::Literal(std::mem::take(&mut literal)));
+                    }
+                    tokens.push(FormatToken::Field(field));
+                }
+                '}' => {
+                    if chars.as_str().starts_with('}') {
+                        chars.next();
+                        literal.push('}');
+                    } else {
+                        return Err(anyhow!(
+                            "unmatched '}}' in format string, use '}}}}' for a literal '}}'"
+                        ));
+                    }
+                }
+                ch => literal.push(ch),
+            }
+        }
+
+        if !literal.is_empty() {
+            tokens.push(FormatToken::Literal(literal));
+        }
+
+        Ok(Self { tokens })
+    }
+
+    fn fields(&self) -> impl Iterator<Item = &FormatField> {
+        self.tokens.iter().filter_map(|token| match token {
+            FormatToken::Field(field) => Some(field),
+            FormatToken::Literal(_) => None,
+        })
+    }
+}
+
+impl FormatField {
+    fn parse(spec: &str) -> anyhow::Result<Self> {
+        let (spec, padding) = match spec.rsplit_once(':') {
+            Some((rest, "")) => (rest, Padding::None),
+            Some((rest, padding_str)) => match Padding::parse(padding_str) {
+                Some(padding) => (rest, padding),
+                None => (spec, Padding::None),
+            },
+            None => match Padding::parse(spec) {
+                Some(_) => return Err(anyhow!("missing field name in '{{{}}}'", spec)),
+                None => (spec, Padding::None),
+            },
+        };
+        let (name, modifier) = match spec.split_once(':') {
+            Some((name, modifier)) => (name, Some(modifier)),
+            None => (spec, None),
+        };
+
+        let kind = match name {
+            "name" => FieldKind::Name,
+            "path" => FieldKind::Path,
+            "inode" => FieldKind::Inode,
+            "blocks" => FieldKind::Blocks,
+            "mode" => FieldKind::Mode,
+            "nlink" => FieldKind::Nlink,
+            "owner" | "user" => FieldKind::Owner,
+            "group" => FieldKind::Group,
+            "size" => match modifier {
+                None => FieldKind::Size(None),
+                Some("raw") => FieldKind::Size(Some(SizeFormat::Raw)),
+                Some("h") => FieldKind::Size(Some(SizeFormat::HumanReadable)),
+                Some("si") => FieldKind::Size(Some(SizeFormat::Si)),
+                Some("iec") => FieldKind::Size(Some(SizeFormat::Iec)),
+                Some(modifier) => {
+                    return Err(anyhow!(
+                        "'{}' is an invalid modifier for '{{size}}'\n\
+                         possible modifiers are ['raw', 'h', 'si', 'iec']",
+                        modifier
+                    ));
+                }
+            },
+            "time" | "atime" | "btime" | "ctime" | "mtime" => {
+                let timestamp_used = match name {
+                    "atime" => Some(TimestampUsed::Accessed),
+                    "btime" => Some(TimestampUsed::Created),
+                    "ctime" => Some(TimestampUsed::Changed),
+                    "mtime" => Some(TimestampUsed::Modified),
+                    _ => None,
+                };
+                if let Some(strftime_format) = modifier {
+                    if !is_valid_strftime_format(strftime_format) {
+                        return Err(anyhow!(
+                            "'{}' is an invalid strftime format for '{{{}}}'",
+                            strftime_format,
+                            name
+                        ));
+                    }
+                }
+
+                FieldKind::Timestamp(timestamp_used, modifier.map(CompactString::from))
+            }
+            _ => {
+                return Err(anyhow!(
+                    "'{}' is an invalid field in format string\n\
+                     possible fields are ['name', 'path', 'inode', 'blocks', 'mode', 'nlink', \
+                     'owner', 'user', 'group', 'size', 'time', 'atime', 'btime', 'ctime', 'mtime']",
+                    name
+                ));
+            }
+        };
+
+        if modifier.is_some() && !matches!(kind, FieldKind::Size(_) | FieldKind::Timestamp(..)) {
+            return Err(anyhow!("'{{{}}}' does not accept a modifier", name));
+        }
+
+        Ok(Self { kind, padding })
+    }
+
+    fn cell(&self, entrybuf: &EntryBuf, config: &Config) -> GridCell {
+        match &self.kind {
+            FieldKind::Name => entrybuf.file_name_cell(config),
+            FieldKind::Path => quote_file_name(entrybuf.path().as_os_str(), config),
+            FieldKind::Inode => entrybuf.ino_cell(config),
+            FieldKind::Blocks => entrybuf.allocated_size_cell(config),
+            FieldKind::Mode => entrybuf.mode_cell(config),
+            FieldKind::Nlink => entrybuf.nlink_cell(config),
+            FieldKind::Owner => entrybuf.owner_cell(config),
+            FieldKind::Group => entrybuf.group_cell(config),
+            FieldKind::Size(None) => entrybuf.size_cell(config),
+            FieldKind::Size(Some(size_format)) => match entrybuf.size() {
+                Some(size) => format_size_with_size_format(size, size_format, config),
+                None => GridCell::error_cell(Alignment::Right),
+            },
+            FieldKind::Timestamp(timestamp_used, strftime_format) => {
+                let timestamp = match timestamp_used {
+                    Some(timestamp_used) => entrybuf.timestamp_of(timestamp_used),
+                    None => entrybuf.timestamp(),
+                };
+
+                match (timestamp, strftime_format) {
+                    (Some(timestamp), Some(strftime_format)) => {
+                        format_timestamp_with_strftime_format(timestamp, strftime_format, config)
+                    }
+                    (Some(timestamp), None) => format_timestamp(timestamp, config),
+                    (None, _) => GridCell::error_cell(Alignment::Left),
+                }
+            }
+        }
+    }
+}
+
+impl Padding {
+    fn parse(padding_str: &str) -> Option<Self> {
+        let mut chars = padding_str.chars();
+        let align = chars.next()?;
+        let width_str = chars.as_str();
+        let width = if width_str.is_empty() {
+            None
+        } else {
+            Some(width_str.parse::<usize>().ok()?)
+        };
+
+        match align {
+            '<' => Some(Self::Left(width)),
+            '>' => Some(Self::Right(width)),
+            _ => None,
+        }
+    }
+}
+
+/// Prints each entry on its own line as described by the configured format string.
+pub fn format_string_format(entrybuf_vec: &[EntryBuf], config: &Config) {
+    let format_string = &config.format_string;
+    let num_fields = format_string.fields().count();
+    let mut cells_vec: Vec<GridCell> = Vec::with_capacity(entrybuf_vec.len() * num_fields);
+    let mut max_width_vec: Vec<usize> = vec![0; num_fields];
+
+    for entrybuf in entrybuf_vec {
+        for (i, field) in format_string.fields().enumerate() {
+            let cell = field.cell(entrybuf, config);
+            max_width_vec[i] = max_width_vec[i].max(cell.width);
+            cells_vec.push(cell);
+        }
+    }
+
+    let mut buffer: Vec<u8> = Vec::with_capacity(entrybuf_vec.len() * 64);
+    for i in 0..entrybuf_vec.len() {
+        let entry_cells = &cells_vec[(i * num_fields)..((i + 1) * num_fields)];
+        let mut field_index: usize = 0;
+        for token in &format_string.tokens {
+            match token {
+                FormatToken::Literal(literal) => buffer.extend_from_slice(literal.as_bytes()),
+                FormatToken::Field(field) => {
+                    let cell = &entry_cells[field_index];
+                    let (pad_width, is_right_aligned) = match field.padding {
+                        Padding::None => (0, false),
+                        Padding::Left(width) => {
+                            (width.unwrap_or(max_width_vec[field_index]), false)
+                        }
+                        Padding::Right(width) => {
+                            (width.unwrap_or(max_width_vec[field_index]), true)
+                        }
+                    };
+                    let padding = " ".repeat(pad_width.saturating_sub(cell.width));
+
+                    if is_right_aligned {
+                        buffer.extend_from_slice(padding.as_bytes());
+                        buffer.extend_from_slice(cell.bytes());
+                    } else {
+                        buffer.extend_from_slice(cell.bytes());
+                        buffer.extend_from_slice(padding.as_bytes());
+                    }
+                    field_index += 1;
+                }
+            }
+        }
+        buffer.push(b'\n');
+    }
+
+    print_bytes(&buffer);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_string_parse() {
+        let format_string = FormatString::parse("{mode} {size:h:>} {{{mtime:%H:%M}}}").unwrap();
+        let tokens = &format_string.tokens;
+
+        assert_eq!(tokens.len(), 6);
+        assert!(matches!(
+            &tokens[0],
+            FormatToken::Field(FormatField {
+                kind: FieldKind::Mode,
+                padding: Padding::None
+            })
+        ));
+        assert!(matches!(&tokens[1], FormatToken::Literal(literal) if *literal == " "));
+        assert!(matches!(
+            &tokens[2],
+            FormatToken::Field(FormatField {
+                kind: FieldKind::Size(Some(SizeFormat::HumanReadable)),
+                padding: Padding::Right(None)
+            })
+        ));
+        assert!(matches!(&tokens[3], FormatToken::Literal(literal) if *literal == " {"));
+        assert!(matches!(
+            &tokens[4],
+            FormatToken::Field(FormatField {
+                kind: FieldKind::Timestamp(Some(TimestampUsed::Modified), Some(strftime_format)),
+                padding: Padding::None
+            }) if *strftime_format == "%H:%M"
+        ));
+        assert!(matches!(&tokens[5], FormatToken::Literal(literal) if *literal == "}"));
+    }
+
+    #[test]
+    fn test_format_string_parse_padding_width() {
+        let format_string = FormatString::parse("{name:<20}").unwrap();
+
+        assert!(matches!(
+            &format_string.tokens[0],
+            FormatToken::Field(FormatField {
+                kind: FieldKind::Name,
+                padding: Padding::Left(Some(20))
+            })
+        ));
+    }
+
+    #[test]
+    fn test_format_string_parse_strftime_format_with_colons() {
+        let strftime_format_and_padding =
+            |format: &str| match FormatString::parse(format).unwrap().tokens.remove(0) {
+                FormatToken::Field(FormatField {
+                    kind: FieldKind::Timestamp(_, strftime_format),
+                    padding,
+                }) => (strftime_format.unwrap(), padding),
+                token => panic!("{:?} is not a timestamp field", token),
+            };
+
+        assert_eq!(
+            strftime_format_and_padding("{mtime:%H:%M:%S}"),
+            ("%H:%M:%S".into(), Padding::None)
+        );
+        assert_eq!(
+            strftime_format_and_padding("{mtime:%H:%M:>}"),
+            ("%H:%M".into(), Padding::Right(None))
+        );
+        // The last part is always the padding, which may be empty
+        assert_eq!(
+            strftime_format_and_padding("{mtime:%H:<}"),
+            ("%H".into(), Padding::Left(None))
+        );
+        assert_eq!(
+            strftime_format_and_padding("{mtime:%H:<:}"),
+            ("%H:<".into(), Padding::None)
+        );
+    }
+
+    #[test]
+    fn test_format_string_parse_invalid() {
+        assert!(FormatString::parse("{unknown}").is_err());
+        assert!(FormatString::parse("{name").is_err());
+        assert!(FormatString::parse("name}").is_err());
+        assert!(FormatString::parse("{size:x}").is_err());
+        assert!(FormatString::parse("{mode:h}").is_err());
+        assert!(FormatString::parse("{mtime:%Q}").is_err());
+        assert!(FormatString::parse("{:>8}").is_err());
+    }
+}
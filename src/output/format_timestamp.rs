@@ -4,19 +4,21 @@ use std::slice;
 use std::str;
 use std::time::SystemTime;
 
-use chrono::offset::{Local, TimeZone};
+use chrono::format::{Item, StrftimeItems};
+use chrono::offset::{FixedOffset, Local, TimeZone};
 use chrono::{DateTime, Datelike, LocalResult, Timelike};
-use nls_term_grid::Alignment;
+use compact_str::{CompactString, ToCompactString};
 use once_cell::sync::OnceCell;
 
-use crate::config::Config;
-use crate::output::{GridCell, GridCellExts};
-use crate::utils::systemtime_to_unix_timestamp;
+use crate::config::{Config, TimeStyle, TimeZoneSetting};
+use crate::output::{Alignment, GridCell, GridCellExts};
+use crate::utils::{systemtime_to_unix_timestamp, UnixTimestamp};
 
-/// Formats a timestamp into a left aligned `GridCell`.
+/// Formats a timestamp into a left aligned `GridCell` using the configured time style
+/// and time zone.
 ///
-/// If timestamp is within 6 months ago from now, the
-/// format will be equivalent to the output of:
+/// For the default time style in the C/POSIX locale, if timestamp is within 6 months
+/// ago from now, the format will be equivalent to the output of:
 /// ```sh
 /// date "+%b %e %H:%M"
 /// ```
@@ -26,28 +28,220 @@ use crate::utils::systemtime_to_unix_timestamp;
 /// date "+%b %e  %Y"
 /// ```
 ///
+/// Other locales use the month names and date order of their built-in table.
+///
+/// If '--tz-offset' is set, the UTC offset of the timestamp is appended e.g. `+0530`.
+///
 /// If timestamp is invalid e.g. timestamp is out of range,
 /// a left aligned error `GridCell` is returned instead.
-pub fn format_timestamp(timestamp: i64, config: &Config) -> GridCell {
-    match Local.timestamp_opt(timestamp, 0) {
-        LocalResult::Single(datetime) => {
-            static SIX_MONTHS_AGO_TIMESTAMP: OnceCell<i64> = OnceCell::new();
+pub fn format_timestamp(timestamp: UnixTimestamp, config: &Config) -> GridCell {
+    let now = now_timestamp();
+    let mut timestamp_cell = match &config.time_style {
+        TimeStyle::Locale => match to_datetime(timestamp, config) {
+            Some(datetime) => {
+                let timestamp_style = config.theme.timestamp_style();
+
+                match config.time_locale {
+                    Some(time_locale) => GridCell::from_str_with_style(
+                        &time_locale.format(&datetime, is_recent(timestamp.secs, now)),
+                        timestamp_style,
+                    ),
+                    None => {
+                        let mut buffer = TimestampBuffer::new();
+
+                        let timestamp_str = if is_recent(timestamp.secs, now) {
+                            buffer.format_recent(datetime)
+                        } else {
+                            buffer.format_old(datetime)
+                        };
+
+                        GridCell::from_ascii_str_with_style(timestamp_str, timestamp_style)
+                    }
+                }
+            }
+            None => return GridCell::error_cell(Alignment::Left),
+        },
+        // The UTC offset is already part of the full-iso time style
+        TimeStyle::FullIso => {
+            return format_timestamp_with_strftime_format(
+                timestamp,
+                "%Y-%m-%d %H:%M:%S%.9f %z",
+                config,
+            );
+        }
+        TimeStyle::LongIso => {
+            format_timestamp_with_strftime_format(timestamp, "%Y-%m-%d %H:%M", config)
+        }
+        TimeStyle::Iso => {
+            if is_recent(timestamp.secs, now) {
+                format_timestamp_with_strftime_format(timestamp, "%m-%d %H:%M", config)
+            } else {
+                format_timestamp_with_strftime_format(timestamp, "%Y-%m-%d ", config)
+            }
+        }
+        TimeStyle::Format { recent, old } => {
+            if is_recent(timestamp.secs, now) {
+                format_timestamp_with_strftime_format(timestamp, recent, config)
+            } else {
+                format_timestamp_with_strftime_format(timestamp, old, config)
+            }
+        }
+        // The age of a timestamp does not depend on the time zone
+        TimeStyle::Relative => {
+            return GridCell::from_ascii_str_with_style(
+                &format_relative_timestamp(timestamp.secs, now, false),
+                config.theme.timestamp_style(),
+            );
+        }
+        TimeStyle::RelativeCompact => {
+            return GridCell::from_ascii_str_with_style(
+                &format_relative_timestamp(timestamp.secs, now, true),
+                config.theme.timestamp_style(),
+            );
+        }
+    };
+
+    if config.show_tz_offset {
+        if let Some(datetime) = to_datetime(timestamp, config) {
+            timestamp_cell.push_char(' ');
+            timestamp_cell.append(GridCell::from_ascii_str_with_style(
+                &datetime.format("%z").to_compact_string(),
+                config.theme.timestamp_style(),
+            ));
+        }
+    }
+
+    timestamp_cell
+}
+
+/// Converts a timestamp into a datetime in the time zone set by '--tz' or `TZ`.
+fn to_datetime(timestamp: UnixTimestamp, config: &Config) -> Option<DateTime<FixedOffset>> {
+    match &config.time_zone {
+        TimeZoneSetting::Local | TimeZoneSetting::Named(_) => {
+            match Local.timestamp_opt(timestamp.secs, timestamp.nsecs) {
+                LocalResult::Single(datetime) => Some(datetime.fixed_offset()),
+                _ => None,
+            }
+        }
+        TimeZoneSetting::Fixed(offset) => {
+            match offset.timestamp_opt(timestamp.secs, timestamp.nsecs) {
+                LocalResult::Single(datetime) => Some(datetime),
+                _ => None,
+            }
+        }
+    }
+}
+
+/// Formats the age of a timestamp relative to `now` e.g. `3 minutes ago` or `in 5 hours`,
+/// or `3m` and `in 5h` if `compact` is true.
+fn format_relative_timestamp(timestamp: i64, now: i64, compact: bool) -> String {
+    const UNITS: [(i64, &str, &str); 6] = [
+        (60 * 60 * 24 * 365, "year", "y"),
+        (60 * 60 * 24 * 30, "month", "mo"),
+        (60 * 60 * 24, "day", "d"),
+        (60 * 60, "hour", "h"),
+        (60, "minute", "m"),
+        (1, "second", "s"),
+    ];
+
+    let is_future = timestamp > now;
+    let age = timestamp.abs_diff(now);
+    let (unit_secs, unit_name, unit_abbr) = UNITS
+        .iter()
+        .find(|(unit_secs, _, _)| age >= *unit_secs as u64)
+        .unwrap_or(&UNITS[UNITS.len() - 1]);
+    let count = age / (*unit_secs as u64);
+
+    let age_str = if compact {
+        format!("{}{}", count, unit_abbr)
+    } else if count == 1 {
+        format!("{} {}", count, unit_name)
+    } else {
+        format!("{} {}s", count, unit_name)
+    };
+
+    match (is_future, compact) {
+        (true, _) => format!("in {}", age_str),
+        (false, true) => age_str,
+        (false, false) => format!("{} ago", age_str),
+    }
+}
+
+/// Returns true if timestamp is within 6 months ago from `now`.
+fn is_recent(timestamp: i64, now: i64) -> bool {
+    timestamp > get_six_months_ago_timestamp(now)
+}
+
+/// Returns the unix timestamp of now, which is the same for the whole listing.
+fn now_timestamp() -> i64 {
+    static NOW_TIMESTAMP: OnceCell<i64> = OnceCell::new();
+
+    *NOW_TIMESTAMP.get_or_init(|| {
+        systemtime_to_unix_timestamp(Ok(SystemTime::now()))
+            .unwrap()
+            .secs
+    })
+}
+
+/// Formats a timestamp into a left aligned `GridCell` using a strftime format string.
+///
+/// `strftime_format` must have been checked with [`is_valid_strftime_format`] beforehand.
+///
+/// If timestamp is invalid e.g. timestamp is out of range,
+/// a left aligned error `GridCell` is returned instead.
+pub fn format_timestamp_with_strftime_format(
+    timestamp: UnixTimestamp,
+    strftime_format: &str,
+    config: &Config,
+) -> GridCell {
+    match to_datetime(timestamp, config) {
+        Some(datetime) => {
             let timestamp_style = config.theme.timestamp_style();
-            let mut buffer = TimestampBuffer::new();
+            let timestamp_string = datetime.format(strftime_format).to_compact_string();
 
-            let six_months_ago_timestamp =
-                SIX_MONTHS_AGO_TIMESTAMP.get_or_init(get_six_months_ago_timestamp);
+            GridCell::from_str_with_style(&timestamp_string, timestamp_style)
+        }
+        None => GridCell::error_cell(Alignment::Left),
+    }
+}
 
-            let timestamp_str = if timestamp > *six_months_ago_timestamp {
-                buffer.format_recent(datetime)
-            } else {
-                buffer.format_old(datetime)
-            };
+/// Converts a strftime format of GNU ls into the format understood by chrono,
+/// where the nanoseconds `%N` and e.g. the milliseconds `%3N` are written `%9f` and `%3f`.
+pub fn to_chrono_strftime_format(strftime_format: &str) -> CompactString {
+    let mut chrono_format = CompactString::with_capacity(strftime_format.len());
+    let mut chars = strftime_format.chars();
+    while let Some(ch) = chars.next() {
+        chrono_format.push(ch);
+        if ch != '%' {
+            continue;
+        }
 
-            GridCell::from_ascii_str_with_style(timestamp_str, timestamp_style)
+        let specifier: CompactString = chars
+            .clone()
+            .take_while(|ch| ch.is_ascii_digit() || *ch == 'N')
+            .collect();
+        match specifier.find('N') {
+            Some(index) => {
+                let digits = &specifier[..index];
+                chrono_format.push_str(if digits.is_empty() { "9" } else { digits });
+                chrono_format.push('f');
+                chars.nth(index);
+            }
+            // The escaped '%' of '%%' is not the start of a specifier
+            None => {
+                if let Some(ch) = chars.next() {
+                    chrono_format.push(ch);
+                }
+            }
         }
-        _ => GridCell::error_cell(Alignment::Left),
     }
+
+    chrono_format
+}
+
+/// Returns true if `strftime_format` only contains valid strftime specifiers.
+pub fn is_valid_strftime_format(strftime_format: &str) -> bool {
+    StrftimeItems::new(strftime_format).all(|item| item != Item::Error)
 }
 
 /// A correctly sized stack allocated array of bytes for the formatted timestamp to be written into.
@@ -79,7 +273,7 @@ impl TimestampBuffer {
     /// ```sh
     /// date "+%b %e %H:%M"
     /// ```
-    fn format_recent(&mut self, datetime: DateTime<Local>) -> &str {
+    fn format_recent<Tz: TimeZone>(&mut self, datetime: DateTime<Tz>) -> &str {
         let bytes_ptr = self.bytes.as_mut_ptr() as *mut u8;
         let mut bytes_len: isize = 0;
 
@@ -118,7 +312,7 @@ impl TimestampBuffer {
     /// ```sh
     /// date "+%b %e  %Y"
     /// ```
-    fn format_old(&mut self, datetime: DateTime<Local>) -> &str {
+    fn format_old<Tz: TimeZone>(&mut self, datetime: DateTime<Tz>) -> &str {
         let bytes_ptr = self.bytes.as_mut_ptr() as *mut u8;
         let mut bytes_len: isize = 0;
 
@@ -224,9 +418,9 @@ const MINUTE_LUT: &[u8] = b"00010203040506070809\
 /// * 3 -> paddings zeros
 const YEAR_PADDING_LUT: &[u8] = b" 000";
 
-/// Returns a unix timestamp 6 months ago from now.
-fn get_six_months_ago_timestamp() -> i64 {
-    systemtime_to_unix_timestamp(Ok(SystemTime::now())).unwrap() - SIX_MONTHS_IN_SECS
+/// Returns a unix timestamp 6 months ago from `now`.
+fn get_six_months_ago_timestamp(now: i64) -> i64 {
+    now - SIX_MONTHS_IN_SECS
 }
 
 const SIX_MONTHS_IN_SECS: i64 = 60 * 60 * 24 * 30 * 6;
@@ -237,7 +431,7 @@ mod tests {
 
     use std::time::Duration;
 
-    use compact_str::{format_compact, ToCompactString};
+    use compact_str::{format_compact, CompactString, ToCompactString};
 
     use crate::config::Config;
     use crate::theme::ThemeConfig;
@@ -245,7 +439,7 @@ mod tests {
     #[test]
     fn test_format_timestamp_recent() {
         let datetime = Local::now();
-        let timestamp = datetime.timestamp();
+        let timestamp = UnixTimestamp::from(datetime.timestamp());
         let config = Config::default();
 
         let correct_timestamp_format = datetime.format("%b %e %H:%M").to_compact_string();
@@ -254,6 +448,7 @@ mod tests {
             contents: correct_timestamp_format,
             width: correct_timestamp_width,
             alignment: Alignment::Left,
+            raw_contents: None,
         };
 
         assert_eq!(format_timestamp(timestamp, &config), correct_timestamp_cell);
@@ -262,7 +457,7 @@ mod tests {
     #[test]
     fn test_format_timestamp_recent_with_style() {
         let datetime = Local::now();
-        let timestamp = datetime.timestamp();
+        let timestamp = UnixTimestamp::from(datetime.timestamp());
         let config = Config {
             theme: ThemeConfig::with_default_colors(),
             ..Default::default()
@@ -279,6 +474,7 @@ mod tests {
             ),
             width: correct_timestamp_width,
             alignment: Alignment::Left,
+            raw_contents: None,
         };
 
         assert_eq!(format_timestamp(timestamp, &config), correct_timestamp_cell);
@@ -288,7 +484,7 @@ mod tests {
     fn test_format_timestamp_old() {
         let six_months = Duration::from_secs(SIX_MONTHS_IN_SECS as u64);
         let datetime: DateTime<Local> = DateTime::from(SystemTime::now() - six_months);
-        let timestamp = datetime.timestamp();
+        let timestamp = UnixTimestamp::from(datetime.timestamp());
         let config = Config::default();
 
         let correct_timestamp_format = datetime.format("%b %e  %Y").to_compact_string();
@@ -297,6 +493,7 @@ mod tests {
             contents: correct_timestamp_format,
             width: correct_timestamp_width,
             alignment: Alignment::Left,
+            raw_contents: None,
         };
 
         assert_eq!(format_timestamp(timestamp, &config), correct_timestamp_cell);
@@ -306,7 +503,7 @@ mod tests {
     fn test_format_timestamp_old_with_style() {
         let six_months = Duration::from_secs(SIX_MONTHS_IN_SECS as u64);
         let datetime: DateTime<Local> = DateTime::from(SystemTime::now() - six_months);
-        let timestamp = datetime.timestamp();
+        let timestamp = UnixTimestamp::from(datetime.timestamp());
         let config = Config {
             theme: ThemeConfig::with_default_colors(),
             ..Default::default()
@@ -323,6 +520,7 @@ mod tests {
             ),
             width: correct_timestamp_width,
             alignment: Alignment::Left,
+            raw_contents: None,
         };
 
         assert_eq!(format_timestamp(timestamp, &config), correct_timestamp_cell);
@@ -333,7 +531,219 @@ mod tests {
         let config = Config::default();
         let correct_timestamp_cell = GridCell::error_cell(Alignment::Left);
 
-        assert_eq!(format_timestamp(i64::MAX, &config), correct_timestamp_cell);
+        assert_eq!(
+            format_timestamp(UnixTimestamp::from(i64::MAX), &config),
+            correct_timestamp_cell
+        );
+    }
+
+    #[test]
+    fn test_format_timestamp_with_strftime_format() {
+        let datetime = Local::now();
+        let timestamp = UnixTimestamp::from(datetime.timestamp());
+        let config = Config::default();
+
+        let correct_timestamp_cell = GridCell {
+            contents: datetime.format("%F %H:%M").to_compact_string(),
+            width: 16,
+            alignment: Alignment::Left,
+            raw_contents: None,
+        };
+
+        assert_eq!(
+            format_timestamp_with_strftime_format(timestamp, "%F %H:%M", &config),
+            correct_timestamp_cell
+        );
+    }
+
+    #[test]
+    fn test_format_timestamp_time_style_full_iso() {
+        let datetime = Local::now();
+        let timestamp = UnixTimestamp::new(datetime.timestamp(), 123_456_789);
+        let config = Config {
+            time_style: TimeStyle::FullIso,
+            ..Default::default()
+        };
+
+        let correct_timestamp_format = datetime
+            .format("%Y-%m-%d %H:%M:%S.123456789 %z")
+            .to_compact_string();
+        assert_eq!(
+            format_timestamp(timestamp, &config).contents,
+            correct_timestamp_format
+        );
+    }
+
+    #[test]
+    fn test_format_timestamp_time_style_iso() {
+        let six_months = Duration::from_secs(SIX_MONTHS_IN_SECS as u64);
+        let recent_datetime = Local::now();
+        let old_datetime: DateTime<Local> = DateTime::from(SystemTime::now() - six_months);
+        let config = Config {
+            time_style: TimeStyle::Iso,
+            ..Default::default()
+        };
+
+        let recent_cell = format_timestamp(recent_datetime.timestamp().into(), &config);
+        let old_cell = format_timestamp(old_datetime.timestamp().into(), &config);
+
+        assert_eq!(
+            recent_cell.contents,
+            recent_datetime.format("%m-%d %H:%M").to_compact_string()
+        );
+        assert_eq!(
+            old_cell.contents,
+            old_datetime.format("%Y-%m-%d ").to_compact_string()
+        );
+        assert_eq!(recent_cell.width, old_cell.width);
+    }
+
+    #[test]
+    fn test_format_timestamp_time_style_format() {
+        let six_months = Duration::from_secs(SIX_MONTHS_IN_SECS as u64);
+        let recent_datetime = Local::now();
+        let old_datetime: DateTime<Local> = DateTime::from(SystemTime::now() - six_months);
+        let config = Config {
+            time_style: TimeStyle::parse("+%H:%M\n%Y").unwrap(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            format_timestamp(recent_datetime.timestamp().into(), &config).contents,
+            recent_datetime.format("%H:%M").to_compact_string()
+        );
+        assert_eq!(
+            format_timestamp(old_datetime.timestamp().into(), &config).contents,
+            old_datetime.format("%Y").to_compact_string()
+        );
+    }
+
+    #[test]
+    fn test_format_relative_timestamp() {
+        let now: i64 = 1_700_000_000;
+
+        assert_eq!(format_relative_timestamp(now, now, false), "0 seconds ago");
+        assert_eq!(
+            format_relative_timestamp(now - 1, now, false),
+            "1 second ago"
+        );
+        assert_eq!(
+            format_relative_timestamp(now - 180, now, false),
+            "3 minutes ago"
+        );
+        assert_eq!(
+            format_relative_timestamp(now - 3600, now, false),
+            "1 hour ago"
+        );
+        assert_eq!(
+            format_relative_timestamp(now - 2 * 86400, now, false),
+            "2 days ago"
+        );
+        assert_eq!(
+            format_relative_timestamp(now - 45 * 86400, now, false),
+            "1 month ago"
+        );
+        assert_eq!(
+            format_relative_timestamp(now - 800 * 86400, now, false),
+            "2 years ago"
+        );
+        assert_eq!(
+            format_relative_timestamp(now + 5 * 3600, now, false),
+            "in 5 hours"
+        );
+    }
+
+    #[test]
+    fn test_format_relative_timestamp_compact() {
+        let now: i64 = 1_700_000_000;
+
+        assert_eq!(format_relative_timestamp(now - 180, now, true), "3m");
+        assert_eq!(format_relative_timestamp(now - 2 * 86400, now, true), "2d");
+        assert_eq!(
+            format_relative_timestamp(now - 45 * 86400, now, true),
+            "1mo"
+        );
+        assert_eq!(
+            format_relative_timestamp(now + 5 * 3600, now, true),
+            "in 5h"
+        );
+    }
+
+    #[test]
+    fn test_format_timestamp_time_zone_fixed() {
+        // 2024-01-15 12:00:00 UTC
+        let timestamp = UnixTimestamp::from(1_705_320_000);
+        let mut config = Config {
+            time_style: TimeStyle::LongIso,
+            time_zone: TimeZoneSetting::parse("+05:30").unwrap(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            format_timestamp(timestamp, &config).contents,
+            "2024-01-15 17:30"
+        );
+
+        config.time_zone = TimeZoneSetting::parse("UTC").unwrap();
+        config.show_tz_offset = true;
+        assert_eq!(
+            format_timestamp(timestamp, &config).contents,
+            "2024-01-15 12:00 +0000"
+        );
+
+        config.time_style = TimeStyle::Locale;
+        config.time_zone = TimeZoneSetting::parse("-0800").unwrap();
+        assert_eq!(
+            format_timestamp(timestamp, &config).contents,
+            "Jan 15  2024 -0800"
+        );
+    }
+
+    #[test]
+    fn test_time_style_parse() {
+        assert_eq!(TimeStyle::parse("full-iso"), Some(TimeStyle::FullIso));
+        assert_eq!(TimeStyle::parse("long-iso"), Some(TimeStyle::LongIso));
+        assert_eq!(TimeStyle::parse("iso"), Some(TimeStyle::Iso));
+        assert_eq!(TimeStyle::parse("locale"), Some(TimeStyle::Locale));
+        assert_eq!(TimeStyle::parse("relative"), Some(TimeStyle::Relative));
+        assert_eq!(
+            TimeStyle::parse("relative-compact"),
+            Some(TimeStyle::RelativeCompact)
+        );
+        assert_eq!(
+            TimeStyle::parse("+%F"),
+            Some(TimeStyle::Format {
+                recent: CompactString::new("%F"),
+                old: CompactString::new("%F"),
+            })
+        );
+        assert_eq!(
+            TimeStyle::parse("+%T.%N\n%T.%3N"),
+            Some(TimeStyle::Format {
+                recent: CompactString::new("%T.%9f"),
+                old: CompactString::new("%T.%3f"),
+            })
+        );
+        assert_eq!(TimeStyle::parse("+%F\n%Y\n%m"), None);
+        assert_eq!(TimeStyle::parse("+%Q"), None);
+        assert_eq!(TimeStyle::parse("%F"), None);
+        assert_eq!(TimeStyle::parse("long"), None);
+    }
+
+    #[test]
+    fn test_to_chrono_strftime_format() {
+        assert_eq!(to_chrono_strftime_format("%F %T.%N"), "%F %T.%9f");
+        assert_eq!(to_chrono_strftime_format("%s.%3N"), "%s.%3f");
+        assert_eq!(to_chrono_strftime_format("%%N %N"), "%%N %9f");
+        assert_eq!(to_chrono_strftime_format("100%"), "100%");
+        assert_eq!(to_chrono_strftime_format("%Y-%m-%d"), "%Y-%m-%d");
+    }
+
+    #[test]
+    fn test_is_valid_strftime_format() {
+        assert!(is_valid_strftime_format("%Y-%m-%d %H:%M"));
+        assert!(is_valid_strftime_format("modified on %F"));
+        assert!(!is_valid_strftime_format("%Q"));
     }
 
     #[test]
@@ -400,8 +810,22 @@ mod tests {
     #[test]
     fn test_get_six_months_ago_timestamp() {
         let six_months = Duration::from_secs(SIX_MONTHS_IN_SECS as u64);
-        let datetime = Local::now() - six_months;
+        let now = Local::now();
+        let datetime = now - six_months;
+
+        assert_eq!(
+            get_six_months_ago_timestamp(now.timestamp()),
+            datetime.timestamp()
+        );
+    }
+
+    #[test]
+    fn test_is_recent() {
+        let now: i64 = 1_700_000_000;
 
-        assert_eq!(get_six_months_ago_timestamp(), datetime.timestamp());
+        assert!(is_recent(now, now));
+        assert!(is_recent(now - SIX_MONTHS_IN_SECS + 1, now));
+        assert!(!is_recent(now - SIX_MONTHS_IN_SECS, now));
+        assert!(is_recent(now + 3600, now));
     }
 }
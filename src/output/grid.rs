@@ -0,0 +1,474 @@
+use std::ops::Range;
+
+use compact_str::CompactString;
+
+/// Indicates on which side of the contents of a cell padding is added
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy)]
+pub enum Alignment {
+    /// Padding is added to the right side of the contents
+    #[default]
+    Left,
+    /// Padding is added to the left side of the contents
+    Right,
+}
+
+/// Indicates the order cells are laid out in
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Direction {
+    /// Cells are laid out from left to right, then top to bottom
+    LeftToRight,
+    /// Cells are laid out from top to bottom, then left to right, like `ls -C`
+    TopToBottom,
+}
+
+/// A string with its display width and alignment
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GridCell {
+    /// The string written when displayed
+    pub contents: CompactString,
+    /// The display width of contents in columns
+    pub width: usize,
+    /// Whether contents is (left/right) aligned when padding is required
+    pub alignment: Alignment,
+    /// The bytes written in place of contents if they are not valid UTF-8, such as
+    /// a file name with invalid UTF-8, where contents has a U+FFFD for each invalid byte
+    pub raw_contents: Option<Vec<u8>>,
+}
+
+impl GridCell {
+    /// Returns the bytes written when the cell is displayed.
+    pub fn bytes(&self) -> &[u8] {
+        match &self.raw_contents {
+            Some(raw_contents) => raw_contents,
+            None => self.contents.as_bytes(),
+        }
+    }
+}
+
+/// Lays out cells in columns like `ls`.
+#[derive(Debug)]
+pub struct Grid<'a> {
+    cells: &'a [GridCell],
+    separator: &'a str,
+    direction: Direction,
+    tabsize: usize,
+}
+
+impl<'a> Grid<'a> {
+    pub fn new(separator: &'a str, direction: Direction, cells: &'a [GridCell]) -> Self {
+        Self {
+            cells: cells,
+            separator: separator,
+            direction: direction,
+            tabsize: 0,
+        }
+    }
+
+    /// Pads between left aligned cells with tabs of `tabsize` columns where possible,
+    /// like GNU's '-T'. A tabsize of 0 only pads with spaces.
+    pub fn with_tabsize(mut self, tabsize: usize) -> Self {
+        self.tabsize = tabsize;
+        self
+    }
+
+    /// Returns the grid laid out in `num_columns` columns.
+    pub fn fit_into_columns(&self, num_columns: usize) -> GridDisplay<'_> {
+        GridDisplay {
+            dimensions: self.calculate_dimensions(num_columns.max(1)),
+            grid: self,
+        }
+    }
+
+    /// Returns the grid laid out in as many columns as fit into `display_width`
+    /// while keeping the columns well packed.
+    ///
+    /// Returns `None` if a cell does not fit into `display_width`.
+    pub fn fit_into_width(&self, display_width: usize) -> Option<GridDisplay<'_>> {
+        let max_cell_width: usize = self.cells.iter().map(|cell| cell.width).max().unwrap_or(0);
+        if !self.cells.is_empty() && max_cell_width >= display_width {
+            return None;
+        }
+
+        let separator_width = self.separator.len();
+        let total_width: usize = self.cells.iter().map(|cell| cell.width).sum::<usize>()
+            + self.cells.len().saturating_sub(1) * separator_width;
+        if total_width <= display_width {
+            return Some(self.fit_into_columns(self.cells.len()));
+        }
+
+        // Start from the number of columns of the widest cell, at least one,
+        // then look for the best packed dimensions with more columns
+        let mut num_columns = (display_width / (max_cell_width + separator_width)).max(1);
+        let mut dimensions = self.calculate_dimensions(num_columns);
+        loop {
+            num_columns += 1;
+            let new_dimensions = self.calculate_dimensions(num_columns);
+
+            if new_dimensions.total_width(separator_width) > display_width {
+                break;
+            } else if new_dimensions.is_well_packed(self.cells.len(), dimensions.num_rows) {
+                dimensions = new_dimensions;
+            }
+        }
+
+        Some(GridDisplay {
+            dimensions: dimensions,
+            grid: self,
+        })
+    }
+
+    fn calculate_dimensions(&self, num_columns: usize) -> Dimensions {
+        let num_rows = (self.cells.len() + num_columns - 1) / num_columns;
+        let mut column_widths: Vec<usize> = vec![0; num_columns];
+
+        for (cell_index, cell) in self.cells.iter().enumerate() {
+            let column_index = match self.direction {
+                Direction::LeftToRight => cell_index % num_columns,
+                Direction::TopToBottom => cell_index / num_rows,
+            };
+            column_widths[column_index] = column_widths[column_index].max(cell.width);
+        }
+
+        Dimensions {
+            num_rows: num_rows,
+            column_widths: column_widths,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Dimensions {
+    num_rows: usize,
+    column_widths: Vec<usize>,
+}
+
+impl Dimensions {
+    fn total_width(&self, separator_width: usize) -> usize {
+        self.column_widths.iter().sum::<usize>() + (self.column_widths.len() - 1) * separator_width
+    }
+
+    /// Dimensions are well packed if the last column has no more cells than there are
+    /// rows, and they have fewer rows than the previous well packed dimensions, which
+    /// would otherwise be as tall with fewer columns.
+    fn is_well_packed(&self, cell_count: usize, previous_num_rows: usize) -> bool {
+        let last_column_cell_count = cell_count % (self.column_widths.len() - 1);
+
+        (last_column_cell_count <= self.num_rows) && (self.num_rows != previous_num_rows)
+    }
+}
+
+/// A [`Grid`] laid out into rows and columns.
+#[derive(Debug)]
+pub struct GridDisplay<'grid> {
+    dimensions: Dimensions,
+    grid: &'grid Grid<'grid>,
+}
+
+impl GridDisplay<'_> {
+    pub fn num_rows(&self) -> usize {
+        self.dimensions.num_rows
+    }
+
+    pub fn num_columns(&self) -> usize {
+        self.dimensions.column_widths.len()
+    }
+
+    fn cell_index_at(&self, row_index: usize, column_index: usize) -> Option<usize> {
+        if column_index >= self.num_columns() {
+            return None;
+        }
+
+        let cell_index = match self.grid.direction {
+            Direction::LeftToRight => row_index * self.num_columns() + column_index,
+            Direction::TopToBottom => row_index + self.num_rows() * column_index,
+        };
+        if cell_index < self.grid.cells.len() {
+            Some(cell_index)
+        } else {
+            None
+        }
+    }
+
+    /// Writes each row of the grid followed by a newline to `buffer`.
+    ///
+    /// The last cell of a row is written without trailing padding or separator.
+    pub fn write_to(&self, buffer: &mut Vec<u8>) {
+        if self.grid.cells.is_empty() {
+            buffer.push(b'\n');
+            return;
+        }
+
+        self.write_rows_to(buffer, 0..self.num_rows(), |_, _| {});
+    }
+
+    /// Like [`write_to`](Self::write_to), but only writes the rows in `row_range` and
+    /// calls `on_cell_written` with the index of each cell and the offset in `buffer`
+    /// its contents are written at.
+    pub fn write_rows_to<F: FnMut(usize, usize)>(
+        &self,
+        buffer: &mut Vec<u8>,
+        row_range: Range<usize>,
+        mut on_cell_written: F,
+    ) {
+        let separator_width = self.grid.separator.len();
+        for row_index in row_range {
+            let mut column_start: usize = 0;
+            for (column_index, column_width) in self.dimensions.column_widths.iter().enumerate() {
+                let cell_index = match self.cell_index_at(row_index, column_index) {
+                    Some(cell_index) => cell_index,
+                    None => break,
+                };
+                let cell = &self.grid.cells[cell_index];
+                let pad_width = column_width.saturating_sub(cell.width);
+                let is_last_in_row = self.cell_index_at(row_index, column_index + 1).is_none();
+
+                if cell.alignment == Alignment::Right {
+                    push_spaces(buffer, pad_width);
+                }
+                on_cell_written(cell_index, buffer.len());
+                buffer.extend_from_slice(cell.bytes());
+                if !is_last_in_row {
+                    if cell.alignment == Alignment::Left && self.grid.tabsize != 0 {
+                        push_padding(
+                            buffer,
+                            column_start + cell.width,
+                            column_start + column_width + separator_width,
+                            self.grid.tabsize,
+                        );
+                    } else {
+                        if cell.alignment == Alignment::Left {
+                            push_spaces(buffer, pad_width);
+                        }
+                        buffer.extend_from_slice(self.grid.separator.as_bytes());
+                    }
+                }
+
+                column_start += column_width + separator_width;
+            }
+            buffer.push(b'\n');
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffer: Vec<u8> = Vec::with_capacity(self.grid.cells.len() * 32);
+        self.write_to(&mut buffer);
+
+        buffer
+    }
+}
+
+fn push_spaces(buffer: &mut Vec<u8>, num_spaces: usize) {
+    buffer.resize(buffer.len() + num_spaces, b' ');
+}
+
+/// Pads from column `from` to column `to` with tabs where a tab stop is passed, like GNU ls.
+fn push_padding(buffer: &mut Vec<u8>, mut from: usize, to: usize, tabsize: usize) {
+    while from < to {
+        if to / tabsize > (from + 1) / tabsize {
+            buffer.push(b'\t');
+            from += tabsize - from % tabsize;
+        } else {
+            buffer.push(b' ');
+            from += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells_from(names: &[&str]) -> Vec<GridCell> {
+        names
+            .iter()
+            .map(|name| GridCell {
+                contents: CompactString::from(*name),
+                width: name.len(),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn grid_string(display: GridDisplay) -> String {
+        String::from_utf8(display.to_bytes()).unwrap()
+    }
+
+    const FILE_NAMES: [&str; 15] = [
+        "file10", "file20", "file3", "file400", "file5", "file100", "file2", "file30", "file4",
+        "file500", "file1", "file200", "file300", "file40", "file50",
+    ];
+
+    #[test]
+    fn test_fit_into_width_no_cells() {
+        let grid = Grid::new("  ", Direction::TopToBottom, &[]);
+        assert_eq!(grid_string(grid.fit_into_width(80).unwrap()), "\n");
+    }
+
+    #[test]
+    fn test_fit_into_width_one_line() {
+        let cells = cells_from(&FILE_NAMES[..5]);
+        let grid = Grid::new("  ", Direction::TopToBottom, &cells);
+
+        assert_eq!(
+            grid_string(grid.fit_into_width(37).unwrap()),
+            "file10  file20  file3  file400  file5\n"
+        );
+    }
+
+    #[test]
+    fn test_fit_into_width_cell_wider_than_display_width() {
+        let cells = cells_from(&["file1", "file11", "file111"]);
+        let grid = Grid::new("  ", Direction::TopToBottom, &cells);
+
+        assert!(grid.fit_into_width(6).is_none());
+    }
+
+    #[test]
+    fn test_fit_into_width_lefttoright() {
+        let cells = cells_from(&FILE_NAMES);
+        let grid = Grid::new("  ", Direction::LeftToRight, &cells);
+
+        assert_eq!(
+            grid_string(grid.fit_into_width(35).unwrap()),
+            "file10   file20   file3   file400\n\
+             file5    file100  file2   file30\n\
+             file4    file500  file1   file200\n\
+             file300  file40   file50\n"
+        );
+    }
+
+    #[test]
+    fn test_fit_into_width_toptobottom() {
+        let cells = cells_from(&FILE_NAMES);
+        let grid = Grid::new("  ", Direction::TopToBottom, &cells);
+        let display = grid.fit_into_width(35).unwrap();
+
+        assert_eq!((display.num_rows(), display.num_columns()), (4, 4));
+        assert_eq!(
+            grid_string(display),
+            "file10   file5    file4    file300\n\
+             file20   file100  file500  file40\n\
+             file3    file2    file1    file50\n\
+             file400  file30   file200\n"
+        );
+    }
+
+    #[test]
+    fn test_fit_into_width_narrower_than_widest_cell_and_separator() {
+        // The widest cell with a separator does not fit, but the cell alone does
+        let cells = cells_from(&["123456789", "a", "b", "c", "d"]);
+        let grid = Grid::new("  ", Direction::TopToBottom, &cells);
+
+        assert_eq!(
+            grid_string(grid.fit_into_width(10).unwrap()),
+            "123456789\na\nb\nc\nd\n"
+        );
+    }
+
+    #[test]
+    fn test_fit_into_columns_different_alignments() {
+        let mut cells = cells_from(&FILE_NAMES);
+        for row in cells.chunks_mut(5) {
+            for cell in row.iter_mut().skip(1).step_by(2) {
+                cell.alignment = Alignment::Right;
+            }
+        }
+        let grid = Grid::new("  ", Direction::LeftToRight, &cells);
+
+        assert_eq!(
+            grid_string(grid.fit_into_columns(5)),
+            "file10    file20  file3    file400  file5\n\
+             file100    file2  file30     file4  file500\n\
+             file1    file200  file300   file40  file50\n"
+        );
+    }
+
+    #[test]
+    fn test_fit_into_width_no_trailing_padding() {
+        // The last column has fewer cells than the rows before it
+        let cells = cells_from(&["a", "bb", "c", "d", "e", "f", "g", "h", "i", "j"]);
+        let grid = Grid::new("  ", Direction::TopToBottom, &cells);
+
+        assert_eq!(
+            grid_string(grid.fit_into_columns(3)),
+            "a   e  i\nbb  f  j\nc   g\nd   h\n"
+        );
+    }
+
+    #[test]
+    fn test_write_to_raw_contents() {
+        let mut cells = cells_from(&["caf\u{fffd}", "plain"]);
+        cells[0].width = 4;
+        cells[0].raw_contents = Some(b"caf\xe9".to_vec());
+        let grid = Grid::new("  ", Direction::TopToBottom, &cells);
+
+        assert_eq!(grid.fit_into_columns(1).to_bytes(), b"caf\xe9\nplain\n");
+        assert_eq!(grid.fit_into_columns(2).to_bytes(), b"caf\xe9  plain\n");
+    }
+
+    #[test]
+    fn test_write_rows_to() {
+        let mut cells = cells_from(&[
+            "-rw-r--r--",
+            "12",
+            "caf\u{fffd}",
+            "drwxr-xr-x",
+            "4096",
+            "src",
+        ]);
+        cells[1].alignment = Alignment::Right;
+        cells[2].width = 4;
+        cells[2].raw_contents = Some(b"caf\xe9".to_vec());
+        cells[4].alignment = Alignment::Right;
+        let grid = Grid::new(" ", Direction::LeftToRight, &cells);
+        let display = grid.fit_into_columns(3);
+
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut cell_offset_vec: Vec<(usize, usize)> = Vec::new();
+        display.write_rows_to(&mut buffer, 0..2, |cell_index, offset| {
+            cell_offset_vec.push((cell_index, offset))
+        });
+        assert_eq!(buffer, b"-rw-r--r--   12 caf\xe9\ndrwxr-xr-x 4096 src\n");
+        assert_eq!(
+            cell_offset_vec,
+            [(0, 0), (1, 13), (2, 16), (3, 21), (4, 32), (5, 37)]
+        );
+
+        // Column widths are those of the whole grid
+        let mut buffer: Vec<u8> = b"heading\n".to_vec();
+        display.write_rows_to(&mut buffer, 1..2, |_, _| {});
+        assert_eq!(buffer, b"heading\ndrwxr-xr-x 4096 src\n");
+    }
+
+    #[test]
+    fn test_with_tabsize() {
+        let cells = cells_from(&["aaaaaa", "bb", "ccccccccc", "d"]);
+
+        let grid = Grid::new("  ", Direction::TopToBottom, &cells).with_tabsize(8);
+        assert_eq!(
+            grid_string(grid.fit_into_width(22).unwrap()),
+            "aaaaaa\tccccccccc\nbb\td\n"
+        );
+
+        let grid = Grid::new("  ", Direction::LeftToRight, &cells).with_tabsize(8);
+        assert_eq!(
+            grid_string(grid.fit_into_width(80).unwrap()),
+            "aaaaaa\tbb  ccccccccc  d\n"
+        );
+    }
+
+    #[test]
+    fn test_push_padding() {
+        let mut buffer: Vec<u8> = Vec::new();
+        push_padding(&mut buffer, 5, 16, 8);
+        assert_eq!(buffer, b"\t\t");
+
+        buffer.clear();
+        push_padding(&mut buffer, 17, 20, 8);
+        assert_eq!(buffer, b"   ");
+
+        buffer.clear();
+        push_padding(&mut buffer, 7, 9, 8);
+        assert_eq!(buffer, b"  ");
+    }
+}
@@ -1,8 +1,7 @@
 use compact_str::{format_compact, CompactString, ToCompactString};
-use nls_term_grid::*;
 use unicode_width::UnicodeWidthStr;
 
-use crate::output::GridCell;
+use crate::output::{Alignment, GridCell};
 
 pub trait GridCellExts {
     fn error_cell(alignment: Alignment) -> Self;
@@ -24,6 +23,8 @@ pub trait GridCellExts {
     fn push_char(&mut self, ch: char);
 
     fn push_char_with_style(&mut self, ch: char, ansi_style_str: Option<&str>);
+
+    fn push_raw_byte(&mut self, byte: u8);
 }
 
 impl GridCellExts for GridCell {
@@ -32,6 +33,7 @@ impl GridCellExts for GridCell {
             contents: CompactString::new_inline("?"),
             width: 1,
             alignment: alignment,
+            raw_contents: None,
         }
     }
 
@@ -46,6 +48,7 @@ impl GridCellExts for GridCell {
             contents: contents,
             width: width,
             alignment: Alignment::Left,
+            raw_contents: None,
         }
     }
 
@@ -60,6 +63,7 @@ impl GridCellExts for GridCell {
             contents: contents,
             width: width,
             alignment: Alignment::Left,
+            raw_contents: None,
         }
     }
 
@@ -78,6 +82,7 @@ impl GridCellExts for GridCell {
             contents: contents,
             width: width,
             alignment: Alignment::Right,
+            raw_contents: None,
         }
     }
 
@@ -89,38 +94,67 @@ impl GridCellExts for GridCell {
     }
 
     fn append(&mut self, other: Self) {
+        if let Some(other_raw_contents) = &other.raw_contents {
+            raw_contents_mut(self).extend_from_slice(other_raw_contents);
+        } else if let Some(raw_contents) = &mut self.raw_contents {
+            raw_contents.extend_from_slice(other.contents.as_bytes());
+        }
         self.contents.push_str(&other.contents);
         self.width += other.width;
     }
 
     fn push_str(&mut self, string: &str) {
-        self.contents.push_str(string);
+        push_contents(self, string);
         self.width += UnicodeWidthStr::width(string);
     }
 
     fn push_str_with_width(&mut self, string: &str, width: usize) {
-        self.contents.push_str(string);
+        push_contents(self, string);
         self.width += width;
     }
 
     fn push_char(&mut self, ch: char) {
-        self.contents.push(ch);
+        push_contents(self, ch.encode_utf8(&mut [0; 4]));
         self.width += 1;
     }
 
     fn push_char_with_style(&mut self, ch: char, ansi_style_str: Option<&str>) {
         match ansi_style_str {
             Some(ansi_style_str) => {
-                self.contents
-                    .push_str(&format_compact!("\x1b[{}m{}\x1b[0m", ansi_style_str, ch));
+                push_contents(
+                    self,
+                    &format_compact!("\x1b[{}m{}\x1b[0m", ansi_style_str, ch),
+                );
                 self.width += 1;
             }
             None => {
-                self.contents.push(ch);
+                push_contents(self, ch.encode_utf8(&mut [0; 4]));
                 self.width += 1;
             }
         }
     }
+
+    /// Pushes a byte that is not valid UTF-8, which is displayed as U+FFFD
+    /// of width 1 but written as is.
+    fn push_raw_byte(&mut self, byte: u8) {
+        raw_contents_mut(self).push(byte);
+        self.contents.push(char::REPLACEMENT_CHARACTER);
+        self.width += 1;
+    }
+}
+
+fn push_contents(cell: &mut GridCell, string: &str) {
+    cell.contents.push_str(string);
+    if let Some(raw_contents) = &mut cell.raw_contents {
+        raw_contents.extend_from_slice(string.as_bytes());
+    }
+}
+
+/// Returns the raw contents of a cell, which start as its contents if it has none.
+fn raw_contents_mut(cell: &mut GridCell) -> &mut Vec<u8> {
+    let contents = &cell.contents;
+    cell.raw_contents
+        .get_or_insert_with(|| contents.as_bytes().to_vec())
 }
 
 #[cfg(test)]
@@ -134,6 +168,7 @@ mod tests {
             contents: CompactString::from("?"),
             width: 1,
             alignment: Alignment::Left,
+            raw_contents: None,
         };
         assert_eq!(left_aligned_cell, correct_left_aligned_cell);
 
@@ -142,6 +177,7 @@ mod tests {
             contents: CompactString::from("?"),
             width: 1,
             alignment: Alignment::Right,
+            raw_contents: None,
         };
         assert_eq!(right_aligned_cell, correct_right_aligned_cell);
     }
@@ -153,6 +189,7 @@ mod tests {
             contents: CompactString::from("1,   3"),
             width: 6,
             alignment: Alignment::Left,
+            raw_contents: None,
         };
         assert_eq!(cell_no_style, correct_cell_no_style);
 
@@ -161,6 +198,7 @@ mod tests {
             contents: CompactString::from("\x1b[36m1,   3\x1b[0m"),
             width: 6,
             alignment: Alignment::Left,
+            raw_contents: None,
         };
         assert_eq!(cell_with_style, correct_cell_with_style);
     }
@@ -172,6 +210,7 @@ mod tests {
             contents: CompactString::from("main.rs"),
             width: 7,
             alignment: Alignment::Left,
+            raw_contents: None,
         };
         assert_eq!(cell_no_style, correct_cell_no_style);
 
@@ -180,6 +219,7 @@ mod tests {
             contents: CompactString::from("\x1b[36mmain.rs\x1b[0m"),
             width: 7,
             alignment: Alignment::Left,
+            raw_contents: None,
         };
         assert_eq!(cell_with_style, correct_cell_with_style);
     }
@@ -191,6 +231,7 @@ mod tests {
             contents: CompactString::from("4096"),
             width: 4,
             alignment: Alignment::Right,
+            raw_contents: None,
         };
         assert_eq!(cell_no_style, correct_cell_no_style);
 
@@ -199,6 +240,7 @@ mod tests {
             contents: CompactString::from("\x1b[36m4096\x1b[0m"),
             width: 4,
             alignment: Alignment::Right,
+            raw_contents: None,
         };
         assert_eq!(cell_with_style, correct_cell_with_style);
     }
@@ -213,6 +255,21 @@ mod tests {
         assert_eq!(cell, correct_cell);
     }
 
+    #[test]
+    fn test_gridcellexts_append_raw_bytes() {
+        // "café" with 'é' encoded in Latin-1
+        let mut name_cell = GridCell::from_str_with_style("caf", None);
+        name_cell.push_raw_byte(0xe9);
+        assert_eq!(&*name_cell.contents, "caf\u{fffd}");
+        assert_eq!(name_cell.width, 4);
+
+        let mut cell = GridCell::from_str_with_style("/bin -> ", None);
+        cell.append(name_cell);
+        cell.push_char('/');
+        assert_eq!(cell.bytes(), b"/bin -> caf\xe9/");
+        assert_eq!(cell.width, 13);
+    }
+
     #[test]
     fn test_gridcellexts_push_str() {
         let mut cell = GridCell::from_str_with_style("/bin -> ", None);
@@ -233,6 +290,7 @@ mod tests {
             contents: CompactString::from("/bin -> /usr/bin"),
             width: 11,
             alignment: Alignment::Left,
+            raw_contents: None,
         };
 
         assert_eq!(cell, correct_cell);
@@ -258,6 +316,7 @@ mod tests {
             contents: CompactString::from("drwx\x1b[33;1mr\x1b[0m"),
             width: 5,
             alignment: Alignment::Left,
+            raw_contents: None,
         };
         assert_eq!(cell_with_style, correct_cell_with_style);
     }
@@ -0,0 +1,135 @@
+use std::path::{Component, Path, PathBuf};
+
+use compact_str::CompactString;
+use once_cell::sync::OnceCell;
+
+use crate::utils::os_str_bytes;
+
+/// Returns the OSC 8 escape sequence that starts a hyperlink to `path`,
+/// e.g. `\x1b]8;;file://host/home/user/main.rs\x07`.
+pub fn hyperlink_start(path: &Path) -> CompactString {
+    let mut hyperlink = CompactString::with_capacity(64);
+    hyperlink.push_str("\x1b]8;;file://");
+    hyperlink.push_str(hostname());
+    push_percent_encoded_path(&mut hyperlink, &absolute_path(path));
+    hyperlink.push('\x07');
+
+    hyperlink
+}
+
+/// The OSC 8 escape sequence that ends a hyperlink.
+pub const HYPERLINK_END: &str = "\x1b]8;;\x07";
+
+/// Returns the hostname of the machine, which is empty if it cannot be read.
+fn hostname() -> &'static str {
+    static HOSTNAME: OnceCell<CompactString> = OnceCell::new();
+
+    HOSTNAME.get_or_init(|| {
+        cfg_if::cfg_if! {
+            if #[cfg(unix)] {
+                let mut buffer = [0_u8; 256];
+                let result = unsafe {
+                    libc::gethostname(buffer.as_mut_ptr() as *mut libc::c_char, buffer.len())
+                };
+                if result != 0 {
+                    return CompactString::default();
+                }
+
+                let len = buffer.iter().position(|byte| *byte == 0).unwrap_or(buffer.len());
+                CompactString::from_utf8_lossy(&buffer[..len])
+            } else {
+                CompactString::default()
+            }
+        }
+    })
+}
+
+/// Returns the absolute path of `path` without resolving symlinks, so that
+/// a symlink is linked to itself rather than to its target.
+fn absolute_path(path: &Path) -> PathBuf {
+    let path = if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        match std::env::current_dir() {
+            Ok(current_dir) => current_dir.join(path),
+            Err(_) => path.to_path_buf(),
+        }
+    };
+
+    path.components()
+        .filter(|component| *component != Component::CurDir)
+        .collect()
+}
+
+/// Pushes a path percent-encoded as the path of a `file` URL, where every byte
+/// except unreserved characters and `/` is encoded.
+fn push_percent_encoded_path(buffer: &mut CompactString, path: &Path) {
+    const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+    let path_bytes = os_str_bytes(path.as_os_str());
+    if cfg!(windows) {
+        // e.g. `C:\Users` is linked as `file:///C:/Users`
+        buffer.push('/');
+    }
+
+    for byte in path_bytes.iter() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                buffer.push(*byte as char)
+            }
+            b'\\' if cfg!(windows) => buffer.push('/'),
+            b':' if cfg!(windows) => buffer.push(':'),
+            byte => {
+                buffer.push('%');
+                buffer.push(HEX_DIGITS[(byte >> 4) as usize] as char);
+                buffer.push(HEX_DIGITS[(byte & 0xf) as usize] as char);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_push_percent_encoded_path() {
+        let mut buffer = CompactString::default();
+        push_percent_encoded_path(&mut buffer, Path::new("/home/user/my file#1.rs"));
+        assert_eq!(buffer, "/home/user/my%20file%231.rs");
+
+        let mut buffer = CompactString::default();
+        push_percent_encoded_path(&mut buffer, Path::new("/tmp/café"));
+        assert_eq!(buffer, "/tmp/caf%C3%A9");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_push_percent_encoded_path_latin1() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let mut buffer = CompactString::default();
+        push_percent_encoded_path(&mut buffer, Path::new(OsStr::from_bytes(b"/tmp/caf\xe9")));
+        assert_eq!(buffer, "/tmp/caf%E9");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_absolute_path() {
+        assert_eq!(
+            absolute_path(Path::new("/tmp/./dir/../main.rs")),
+            Path::new("/tmp/dir/../main.rs")
+        );
+        assert!(absolute_path(Path::new("./main.rs")).is_absolute());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_hyperlink_start() {
+        let hyperlink = hyperlink_start(Path::new("/tmp/main.rs"));
+        assert!(hyperlink.starts_with("\x1b]8;;file://"));
+        assert!(hyperlink.ends_with("/tmp/main.rs\x07"));
+    }
+}
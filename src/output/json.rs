@@ -0,0 +1,271 @@
+use std::fs::Metadata;
+#[cfg(unix)]
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
+#[cfg(windows)]
+use std::os::windows::fs::MetadataExt;
+use std::path::Path;
+
+use crate::config::TimestampUsed;
+use crate::entry::EntryBuf;
+
+/// Prints all entries of a directory as a JSON array on a single line.
+pub fn json_format(entrybuf_vec: &[EntryBuf], dir: Option<&Path>) {
+    let mut buffer = String::with_capacity(entrybuf_vec.len() * 384);
+
+    buffer.push('[');
+    for (i, entrybuf) in entrybuf_vec.iter().enumerate() {
+        if i != 0 {
+            buffer.push(',');
+        }
+        push_entrybuf_object(&mut buffer, entrybuf, dir);
+    }
+    buffer.push(']');
+
+    println!("{}", buffer);
+}
+
+/// Prints each entry as a JSON object on its own line.
+pub fn ndjson_format(entrybuf_vec: &[EntryBuf], dir: Option<&Path>) {
+    let mut buffer = String::with_capacity(384);
+
+    for entrybuf in entrybuf_vec {
+        buffer.clear();
+        push_entrybuf_object(&mut buffer, entrybuf, dir);
+        println!("{}", buffer);
+    }
+}
+
+/// Pushes an entry as a JSON object. The keys and their order are
+/// documented in the "JSON output" section of the README.
+fn push_entrybuf_object(buffer: &mut String, entrybuf: &EntryBuf, dir: Option<&Path>) {
+    let metadata = entrybuf.metadata();
+    let path = entrybuf.path();
+
+    buffer.push('{');
+
+    push_key(buffer, "directory", true);
+    push_str_or_null(buffer, dir.map(|dir| dir.to_string_lossy()).as_deref());
+
+    push_key(buffer, "name", false);
+    push_str(buffer, &entrybuf.raw_file_name().to_string_lossy());
+
+    push_key(buffer, "path", false);
+    push_str(buffer, &path.to_string_lossy());
+
+    push_key(buffer, "file_type", false);
+    push_str_or_null(buffer, metadata.map(file_type_str));
+
+    push_key(buffer, "mode", false);
+    push_num_or_null(buffer, metadata.and_then(mode));
+
+    push_key(buffer, "nlink", false);
+    push_num_or_null(buffer, entrybuf.nlink());
+
+    push_key(buffer, "uid", false);
+    push_num_or_null(buffer, metadata.and_then(uid));
+
+    push_key(buffer, "user", false);
+    push_str_or_null(buffer, entrybuf.owner_name().as_deref());
+
+    push_key(buffer, "gid", false);
+    push_num_or_null(buffer, metadata.and_then(gid));
+
+    push_key(buffer, "group", false);
+    push_str_or_null(buffer, entrybuf.group_name().as_deref());
+
+    push_key(buffer, "size", false);
+    push_num_or_null(buffer, entrybuf.size());
+
+    push_key(buffer, "allocated_size", false);
+    push_num_or_null(buffer, entrybuf.allocated_size());
+
+    push_key(buffer, "inode", false);
+    push_num_or_null(buffer, entrybuf.ino());
+
+    // Nanoseconds are separate keys, as a float would round them away
+    for (key, nsec_key, timestamp_used) in [
+        ("atime", "atime_nsec", TimestampUsed::Accessed),
+        ("btime", "btime_nsec", TimestampUsed::Created),
+        ("ctime", "ctime_nsec", TimestampUsed::Changed),
+        ("mtime", "mtime_nsec", TimestampUsed::Modified),
+    ] {
+        let timestamp = entrybuf.timestamp_of(&timestamp_used);
+        push_key(buffer, key, false);
+        push_num_or_null(buffer, timestamp.map(|timestamp| timestamp.secs));
+        push_key(buffer, nsec_key, false);
+        push_num_or_null(buffer, timestamp.map(|timestamp| timestamp.nsecs));
+    }
+
+    push_key(buffer, "symlink_target", false);
+    let symlink_target = match metadata {
+        Some(metadata) if metadata.file_type().is_symlink() => path.read_link().ok(),
+        _ => None,
+    };
+    push_str_or_null(
+        buffer,
+        symlink_target
+            .as_ref()
+            .map(|target| target.to_string_lossy())
+            .as_deref(),
+    );
+
+    buffer.push('}');
+}
+
+fn file_type_str(metadata: &Metadata) -> &'static str {
+    let file_type = metadata.file_type();
+
+    if file_type.is_file() {
+        return "file";
+    } else if file_type.is_dir() {
+        return "directory";
+    } else if file_type.is_symlink() {
+        return "symlink";
+    }
+
+    #[cfg(unix)]
+    if file_type.is_block_device() {
+        return "block_device";
+    } else if file_type.is_char_device() {
+        return "char_device";
+    } else if file_type.is_fifo() {
+        return "fifo";
+    } else if file_type.is_socket() {
+        return "socket";
+    }
+
+    "unknown"
+}
+
+#[cfg(unix)]
+fn mode(metadata: &Metadata) -> Option<u32> {
+    Some(metadata.mode())
+}
+
+#[cfg(windows)]
+fn mode(metadata: &Metadata) -> Option<u32> {
+    Some(metadata.file_attributes())
+}
+
+#[cfg(not(any(unix, windows)))]
+fn mode(_metadata: &Metadata) -> Option<u32> {
+    None
+}
+
+#[cfg(unix)]
+fn uid(metadata: &Metadata) -> Option<u32> {
+    Some(metadata.uid())
+}
+
+#[cfg(not(unix))]
+fn uid(_metadata: &Metadata) -> Option<u32> {
+    None
+}
+
+#[cfg(unix)]
+fn gid(metadata: &Metadata) -> Option<u32> {
+    Some(metadata.gid())
+}
+
+#[cfg(not(unix))]
+fn gid(_metadata: &Metadata) -> Option<u32> {
+    None
+}
+
+fn push_key(buffer: &mut String, key: &str, is_first: bool) {
+    if !is_first {
+        buffer.push(',');
+    }
+    buffer.push('"');
+    buffer.push_str(key);
+    buffer.push_str("\":");
+}
+
+fn push_num_or_null<I: itoa::Integer>(buffer: &mut String, value: Option<I>) {
+    match value {
+        Some(value) => buffer.push_str(itoa::Buffer::new().format(value)),
+        None => buffer.push_str("null"),
+    }
+}
+
+fn push_str_or_null(buffer: &mut String, value: Option<&str>) {
+    match value {
+        Some(value) => push_str(buffer, value),
+        None => buffer.push_str("null"),
+    }
+}
+
+/// Pushes a quoted JSON string, escaping characters as required by RFC 8259.
+fn push_str(buffer: &mut String, value: &str) {
+    use std::fmt::Write;
+
+    buffer.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => buffer.push_str("\\\""),
+            '\\' => buffer.push_str("\\\\"),
+            '\n' => buffer.push_str("\\n"),
+            '\r' => buffer.push_str("\\r"),
+            '\t' => buffer.push_str("\\t"),
+            '\x08' => buffer.push_str("\\b"),
+            '\x0c' => buffer.push_str("\\f"),
+            ch if ch < '\x20' => {
+                let _ = write!(buffer, "\\u{:04x}", ch as u32);
+            }
+            ch => buffer.push(ch),
+        }
+    }
+    buffer.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_str() {
+        let mut buffer = String::new();
+        push_str(&mut buffer, "main.rs");
+        assert_eq!(buffer, r#""main.rs""#);
+    }
+
+    #[test]
+    fn test_push_str_escaped() {
+        let mut buffer = String::new();
+        push_str(&mut buffer, "a\"b\\c\nd\te\x1b");
+        assert_eq!(buffer, r#""a\"b\\c\nd\te\u001b""#);
+    }
+
+    #[test]
+    fn test_push_num_or_null() {
+        let mut buffer = String::new();
+        push_num_or_null(&mut buffer, Some(4096_u64));
+        buffer.push(',');
+        push_num_or_null::<u64>(&mut buffer, None);
+        assert_eq!(buffer, "4096,null");
+    }
+
+    #[test]
+    fn test_push_entrybuf_object() {
+        use crate::config::Config;
+
+        let tmpdir = tempfile::tempdir().unwrap();
+        let path = tmpdir.path().join("file");
+        std::fs::write(&path, b"abc").unwrap();
+        let config = Config::default();
+        let entrybuf = EntryBuf::from_cmdline_path(&path, &config);
+
+        let mut buffer = String::new();
+        push_entrybuf_object(&mut buffer, &entrybuf, Some(tmpdir.path()));
+
+        assert!(buffer.starts_with(&format!(
+            r#"{{"directory":"{}","name":"{}","#,
+            tmpdir.path().display(),
+            path.display()
+        )));
+        assert!(buffer.contains(r#""file_type":"file","#));
+        assert!(buffer.contains(r#""size":3,"#));
+        assert!(buffer.contains(r#","mtime_nsec":"#));
+        assert!(buffer.ends_with(r#""symlink_target":null}"#));
+    }
+}
@@ -1,37 +1,403 @@
-use nls_term_grid::{Direction, Grid};
+use anyhow::anyhow;
+use compact_str::CompactString;
 
-use crate::config::Config;
+use crate::config::{Config, TimestampUsed};
 use crate::entry::EntryBuf;
-use crate::output::GridCell;
+use crate::output::dired::{self, DIRED_INDENT};
+use crate::output::grid::{Direction, Grid};
+use crate::output::tree::prefixed_file_name_cell;
+use crate::output::{
+    format_timestamp, print_bytes, quote_file_name, write_rows_with_group_headings, Alignment,
+    GridCell, GridCellExts, GroupHeading,
+};
 
-pub fn long_format(entrybuf_vec: &[EntryBuf], config: &Config) {
-    let num_columns: usize = 5
-        + (config.list_inode as usize)
-        + (config.list_allocated_size as usize)
-        + (config.list_owner as usize)
-        + (config.list_group as usize);
+/// A column that can be listed in long format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LongColumn {
+    Inode,
+    Blocks,
+    Mode,
+    Nlink,
+    Owner,
+    Group,
+    Size,
+    /// A timestamp, where `None` is the timestamp set with '--time', '-c' or '-u'.
+    Timestamp(Option<TimestampUsed>),
+    Name,
+}
+
+impl LongColumn {
+    /// The registry of column names accepted by '--columns'.
+    const NAMES: &'static [(&'static str, Self)] = &[
+        ("inode", Self::Inode),
+        ("blocks", Self::Blocks),
+        ("mode", Self::Mode),
+        ("nlink", Self::Nlink),
+        ("user", Self::Owner),
+        ("owner", Self::Owner),
+        ("group", Self::Group),
+        ("size", Self::Size),
+        ("time", Self::Timestamp(None)),
+        ("atime", Self::Timestamp(Some(TimestampUsed::Accessed))),
+        ("btime", Self::Timestamp(Some(TimestampUsed::Created))),
+        ("ctime", Self::Timestamp(Some(TimestampUsed::Changed))),
+        ("mtime", Self::Timestamp(Some(TimestampUsed::Modified))),
+        ("name", Self::Name),
+    ];
+
+    /// Returns the column with the given name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::NAMES
+            .iter()
+            .find(|(column_name, _)| *column_name == name)
+            .map(|(_, column)| *column)
+    }
+
+    /// Parses a comma separated list of column names e.g. `inode,mode,size,name`,
+    /// as passed to `option`.
+    pub fn parse_list(list: &str, option: &str) -> anyhow::Result<Vec<Self>> {
+        list.split(',')
+            .map(|name| {
+                Self::from_name(name.trim()).ok_or_else(|| {
+                    let possible_names: Vec<&str> =
+                        Self::NAMES.iter().map(|(name, _)| *name).collect();
+                    anyhow!(
+                        "'{}' is an invalid column for '{}'\n\
+                         possible columns are {:?}",
+                        name,
+                        option,
+                        possible_names
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the columns listed by default, as selected by '-i', '-s', '-g', '-o' and '--time'.
+    pub fn default_columns(config: &Config) -> Vec<Self> {
+        let mut columns: Vec<Self> = Vec::with_capacity(9 + config.extra_timestamps_used.len());
 
-    let mut cells_vec: Vec<GridCell> = Vec::with_capacity(entrybuf_vec.len() * num_columns);
-    for entrybuf in entrybuf_vec {
         if config.list_inode {
-            cells_vec.push(entrybuf.ino_cell(config));
+            columns.push(Self::Inode);
         }
         if config.list_allocated_size {
-            cells_vec.push(entrybuf.allocated_size_cell(config));
+            columns.push(Self::Blocks);
         }
-        cells_vec.push(entrybuf.mode_cell(config));
-        cells_vec.push(entrybuf.nlink_cell(config));
+        columns.push(Self::Mode);
+        columns.push(Self::Nlink);
         if config.list_owner {
-            cells_vec.push(entrybuf.owner_cell(config));
+            columns.push(Self::Owner);
         }
         if config.list_group {
-            cells_vec.push(entrybuf.group_cell(config));
+            columns.push(Self::Group);
+        }
+        columns.push(Self::Size);
+        columns.push(Self::Timestamp(None));
+        for timestamp_used in &config.extra_timestamps_used {
+            columns.push(Self::Timestamp(Some(*timestamp_used)));
+        }
+        columns.push(Self::Name);
+
+        columns
+    }
+
+    /// Returns the name of the column used in headers of machine readable output.
+    pub fn header_name(&self, config: &Config) -> &'static str {
+        match self {
+            Self::Inode => "inode",
+            // Machine readable output lists the allocated size in bytes
+            Self::Blocks => "allocated_size",
+            Self::Mode => "mode",
+            Self::Nlink => "nlink",
+            Self::Owner if config.numeric_uid_gid => "uid",
+            Self::Owner => "user",
+            Self::Group if config.numeric_uid_gid => "gid",
+            Self::Group => "group",
+            Self::Size => "size",
+            Self::Timestamp(timestamp_used) => {
+                match timestamp_used.unwrap_or(config.timestamp_used) {
+                    TimestampUsed::Accessed => "atime",
+                    TimestampUsed::Changed => "ctime",
+                    TimestampUsed::Created => "btime",
+                    TimestampUsed::Modified => "mtime",
+                }
+            }
+            Self::Name => "name",
+        }
+    }
+
+    /// Returns the label of the column printed by '--header'.
+    pub fn header_label(&self, config: &Config) -> &'static str {
+        match self {
+            Self::Inode => "Inode",
+            Self::Blocks => "Blocks",
+            Self::Mode => "Permissions",
+            Self::Nlink => "Links",
+            Self::Owner => "User",
+            Self::Group => "Group",
+            Self::Size => "Size",
+            Self::Timestamp(timestamp_used) => {
+                match timestamp_used.unwrap_or(config.timestamp_used) {
+                    TimestampUsed::Accessed => "Accessed",
+                    TimestampUsed::Changed => "Changed",
+                    TimestampUsed::Created => "Created",
+                    TimestampUsed::Modified => "Modified",
+                }
+            }
+            Self::Name => "Name",
+        }
+    }
+
+    /// Returns the cell printed by '--header', aligned like the values of the column.
+    pub fn header_cell(&self, config: &Config) -> GridCell {
+        let mut header_cell = GridCell::from_ascii_str_with_style(
+            self.header_label(config),
+            config.theme.header_style(),
+        );
+        if matches!(self, Self::Inode | Self::Blocks | Self::Nlink | Self::Size) {
+            header_cell.alignment = Alignment::Right;
+        }
+
+        header_cell
+    }
+
+    pub fn cell(&self, entrybuf: &EntryBuf, config: &Config) -> GridCell {
+        match self {
+            Self::Inode => entrybuf.ino_cell(config),
+            Self::Blocks => entrybuf.allocated_size_cell(config),
+            Self::Mode => entrybuf.mode_cell(config),
+            Self::Nlink => entrybuf.nlink_cell(config),
+            Self::Owner => entrybuf.owner_cell(config),
+            Self::Group => entrybuf.group_cell(config),
+            Self::Size => entrybuf.size_cell(config),
+            Self::Timestamp(None) => entrybuf.timestamp_cell(config),
+            Self::Timestamp(Some(timestamp_used)) => match entrybuf.timestamp_of(timestamp_used) {
+                Some(timestamp) => format_timestamp(timestamp, config),
+                None => GridCell::error_cell(Alignment::Left),
+            },
+            Self::Name => entrybuf.file_name_cell(config),
+        }
+    }
+}
+
+/// Lists entries in long format, with each of `group_heading_vec` before its group.
+pub fn long_format(entrybuf_vec: &[EntryBuf], group_heading_vec: &[GroupHeading], config: &Config) {
+    write_long_format(entrybuf_vec, &[], group_heading_vec, config)
+}
+
+/// Like [`long_format`], but each file name is prefixed with the prefix
+/// at the same index in `name_prefix_vec`.
+pub fn long_format_with_name_prefixes(
+    entrybuf_vec: &[EntryBuf],
+    name_prefix_vec: &[CompactString],
+    config: &Config,
+) {
+    write_long_format(entrybuf_vec, name_prefix_vec, &[], config)
+}
+
+fn write_long_format(
+    entrybuf_vec: &[EntryBuf],
+    name_prefix_vec: &[CompactString],
+    group_heading_vec: &[GroupHeading],
+    config: &Config,
+) {
+    let columns = config.long_columns();
+    let num_columns = columns.len();
+
+    let mut cells_vec: Vec<GridCell> =
+        Vec::with_capacity((entrybuf_vec.len() + config.header as usize) * num_columns);
+    if config.header {
+        for column in &columns {
+            cells_vec.push(column.header_cell(config));
+        }
+    }
+    for (i, entrybuf) in entrybuf_vec.iter().enumerate() {
+        for column in &columns {
+            if *column == LongColumn::Name {
+                cells_vec.push(prefixed_file_name_cell(
+                    entrybuf,
+                    name_prefix_vec.get(i),
+                    config,
+                ));
+            } else {
+                cells_vec.push(column.cell(entrybuf, config));
+            }
         }
-        cells_vec.push(entrybuf.size_cell(config));
-        cells_vec.push(entrybuf.timestamp_cell(config));
-        cells_vec.push(entrybuf.file_name_cell(config));
+    }
+
+    if config.dired {
+        indent_dired_rows(&mut cells_vec, num_columns);
     }
 
     let grid = Grid::new(" ", Direction::LeftToRight, &cells_vec);
-    print!("{}", grid.fit_into_columns(num_columns));
+    let display = grid.fit_into_columns(num_columns);
+    let mut buffer: Vec<u8> = Vec::with_capacity(cells_vec.len() * 16);
+    let num_header_rows = config.header as usize;
+    if config.dired {
+        let mut cell_offset_vec: Vec<usize> = vec![0; cells_vec.len()];
+        write_rows_with_group_headings(
+            &display,
+            &mut buffer,
+            num_header_rows,
+            group_heading_vec,
+            |cell_index, offset| cell_offset_vec[cell_index] = offset,
+        );
+        if let Some(name_column_index) = columns
+            .iter()
+            .position(|column| *column == LongColumn::Name)
+        {
+            for (i, entrybuf) in entrybuf_vec.iter().enumerate() {
+                let cell_index = (i + num_header_rows) * num_columns + name_column_index;
+                // The name cell starts with the indent if it is the first column,
+                // then with the prefix of the file name if any
+                let mut name_start = cell_offset_vec[cell_index]
+                    + name_prefix_vec.get(i).map_or(0, |prefix| prefix.len());
+                if name_column_index == 0 {
+                    name_start += DIRED_INDENT.len();
+                }
+                let name_len = quote_file_name(entrybuf.raw_file_name(), config)
+                    .bytes()
+                    .len();
+                dired::push_file_name(name_start, name_len);
+            }
+        }
+        dired::add_printed_len(buffer.len());
+    } else {
+        write_rows_with_group_headings(
+            &display,
+            &mut buffer,
+            num_header_rows,
+            group_heading_vec,
+            |_, _| {},
+        );
+    }
+
+    print_bytes(&buffer);
+}
+
+/// Indents each row for '--dired' by prefixing its first cell.
+fn indent_dired_rows(cells_vec: &mut [GridCell], num_columns: usize) {
+    for row in cells_vec.chunks_mut(num_columns) {
+        let mut cell = GridCell::from_ascii_str_with_style(DIRED_INDENT, None);
+        cell.alignment = row[0].alignment;
+        cell.append(std::mem::take(&mut row[0]));
+        row[0] = cell;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_long_column_parse_list() {
+        assert_eq!(
+            LongColumn::parse_list("inode,mode,user,size,mtime,btime,name", "--columns").unwrap(),
+            vec![
+                LongColumn::Inode,
+                LongColumn::Mode,
+                LongColumn::Owner,
+                LongColumn::Size,
+                LongColumn::Timestamp(Some(TimestampUsed::Modified)),
+                LongColumn::Timestamp(Some(TimestampUsed::Created)),
+                LongColumn::Name,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_long_column_parse_list_repeated() {
+        assert_eq!(
+            LongColumn::parse_list("name,size,name", "--columns").unwrap(),
+            vec![LongColumn::Name, LongColumn::Size, LongColumn::Name]
+        );
+    }
+
+    #[test]
+    fn test_long_column_parse_list_invalid() {
+        assert!(LongColumn::parse_list("mode,permissions", "--columns").is_err());
+        assert!(LongColumn::parse_list("", "--columns").is_err());
+    }
+
+    #[test]
+    fn test_long_column_default_columns() {
+        let mut config = Config {
+            list_inode: true,
+            list_group: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            LongColumn::default_columns(&config),
+            vec![
+                LongColumn::Inode,
+                LongColumn::Mode,
+                LongColumn::Nlink,
+                LongColumn::Owner,
+                LongColumn::Size,
+                LongColumn::Timestamp(None),
+                LongColumn::Name,
+            ]
+        );
+
+        config.list_owner = false;
+        config.list_allocated_size = true;
+        assert_eq!(
+            LongColumn::default_columns(&config),
+            vec![
+                LongColumn::Inode,
+                LongColumn::Blocks,
+                LongColumn::Mode,
+                LongColumn::Nlink,
+                LongColumn::Size,
+                LongColumn::Timestamp(None),
+                LongColumn::Name,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_long_column_header_cell() {
+        let mut config = Config::default();
+        let mode_header_cell = LongColumn::Mode.header_cell(&config);
+        assert_eq!(&*mode_header_cell.contents, "Permissions");
+        assert_eq!(mode_header_cell.width, 11);
+        assert_eq!(mode_header_cell.alignment, Alignment::Left);
+
+        let size_header_cell = LongColumn::Size.header_cell(&config);
+        assert_eq!(&*size_header_cell.contents, "Size");
+        assert_eq!(size_header_cell.alignment, Alignment::Right);
+
+        config.timestamp_used = TimestampUsed::Accessed;
+        assert_eq!(
+            LongColumn::Timestamp(None).header_label(&config),
+            "Accessed"
+        );
+        assert_eq!(
+            LongColumn::Timestamp(Some(TimestampUsed::Modified)).header_label(&config),
+            "Modified"
+        );
+    }
+
+    #[test]
+    fn test_long_column_default_columns_extra_timestamps() {
+        let config = Config {
+            list_owner: false,
+            list_group: false,
+            timestamp_used: TimestampUsed::Modified,
+            extra_timestamps_used: vec![TimestampUsed::Changed, TimestampUsed::Created],
+            ..Default::default()
+        };
+        assert_eq!(
+            LongColumn::default_columns(&config),
+            vec![
+                LongColumn::Mode,
+                LongColumn::Nlink,
+                LongColumn::Size,
+                LongColumn::Timestamp(None),
+                LongColumn::Timestamp(Some(TimestampUsed::Changed)),
+                LongColumn::Timestamp(Some(TimestampUsed::Created)),
+                LongColumn::Name,
+            ]
+        );
+    }
 }
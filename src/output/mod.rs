@@ -1,45 +1,225 @@
 mod column;
+mod delimited;
+mod dired;
 mod format_filename;
 mod format_size;
+mod format_string;
 mod format_timestamp;
+mod grid;
 mod gridcellexts;
+mod hyperlink;
+mod json;
 mod long;
+mod quote;
 mod sort;
+mod tree;
 
-use crate::config::{Config, OutputFormat};
+use std::io::{self, Write};
+use std::path::Path;
+
+use crate::config::{Config, GroupBy, OutputFormat};
 use crate::entry::EntryBuf;
 
+use dired::DIRED_INDENT;
+
+pub use delimited::print_delimited_header;
+pub use dired::print_dired_offsets;
 pub use format_filename::format_filename;
-pub use format_size::format_size;
-pub use format_timestamp::format_timestamp;
+pub use format_size::{format_size, format_size_with_size_format};
+pub use format_string::FormatString;
+pub use format_timestamp::{format_timestamp, is_valid_strftime_format, to_chrono_strftime_format};
 pub use gridcellexts::GridCellExts;
+pub use long::LongColumn;
+pub use quote::quote_file_name;
+pub use sort::{collation_key, sort_entrybuf_vec};
+pub use tree::tree_format;
 
-pub type GridCell = nls_term_grid::GridCell<compact_str::CompactString>;
+use sort::EntryGroup;
 
-pub fn output(entrybuf_vec: &mut Vec<EntryBuf>, config: &Config) {
-    use column::{across_format, single_column_format, vertical_format};
-    use long::long_format;
-    use sort::sort_entrybuf_vec;
+pub use grid::{Alignment, GridCell};
 
+use grid::GridDisplay;
+
+/// Sorts and prints entries in the configured output format.
+///
+/// `dir` is the directory the entries were listed from,
+/// or `None` if the entries are paths passed on the command line.
+pub fn output(entrybuf_vec: &mut [EntryBuf], dir: Option<&Path>, config: &Config) {
     if entrybuf_vec.is_empty() {
+        // An empty directory is still listed as an empty array
+        if dir.is_some() && config.output_format == OutputFormat::Json {
+            println!("[]");
+        }
         return;
     }
 
-    sort_entrybuf_vec(entrybuf_vec, config);
+    // Paths passed on the command line are kept in the order given by '--no-sort-args'
+    if dir.is_some() || config.sort_args {
+        sort_entrybuf_vec(entrybuf_vec, config);
+    }
+
+    let group_heading_vec = match config.group_by {
+        Some(group_by) if config.group_headings && config.output_format.is_grid_or_long() => {
+            group_headings(entrybuf_vec, group_by, config)
+        }
+        _ => Vec::new(),
+    };
+    output_format(entrybuf_vec, dir, &group_heading_vec, config)
+}
+
+/// A section heading printed by '--group-headings' before the entry at `entry_index`,
+/// which is the first entry of its group.
+pub struct GroupHeading {
+    entry_index: usize,
+    cell: GridCell,
+}
+
+impl GroupHeading {
+    /// Pushes the heading line, separated from the previous group by an empty line.
+    fn push_line(&self, buffer: &mut Vec<u8>) {
+        if self.entry_index != 0 {
+            buffer.push(b'\n');
+        }
+        buffer.extend_from_slice(self.cell.bytes());
+        buffer.push(b'\n');
+    }
+}
+
+/// Returns the heading of each group of sorted entries.
+fn group_headings(
+    entrybuf_vec: &[EntryBuf],
+    group_by: GroupBy,
+    config: &Config,
+) -> Vec<GroupHeading> {
+    let mut group_heading_vec: Vec<GroupHeading> = Vec::new();
+    let mut previous_group: Option<EntryGroup> = None;
+    for (i, entrybuf) in entrybuf_vec.iter().enumerate() {
+        let group = EntryGroup::new(entrybuf, group_by);
+        if previous_group.as_ref() != Some(&group) {
+            group_heading_vec.push(GroupHeading {
+                entry_index: i,
+                cell: GridCell::from_str_with_style(
+                    &group.heading(group_by),
+                    config.theme.header_style(),
+                ),
+            });
+            previous_group = Some(group);
+        }
+    }
+
+    group_heading_vec
+}
+
+/// Writes the rows of a grid with one row per entry after `num_header_rows` rows,
+/// with each group heading before the row of the first entry of its group.
+///
+/// The groups share the column widths of the whole grid.
+fn write_rows_with_group_headings<F: FnMut(usize, usize)>(
+    display: &GridDisplay,
+    buffer: &mut Vec<u8>,
+    num_header_rows: usize,
+    group_heading_vec: &[GroupHeading],
+    mut on_cell_written: F,
+) {
+    let mut row_start: usize = 0;
+    for group_heading in group_heading_vec {
+        let row_end = num_header_rows + group_heading.entry_index;
+        display.write_rows_to(buffer, row_start..row_end, &mut on_cell_written);
+        group_heading.push_line(buffer);
+        row_start = row_end;
+    }
+    display.write_rows_to(buffer, row_start..display.num_rows(), &mut on_cell_written);
+}
+
+fn output_format(
+    entrybuf_vec: &[EntryBuf],
+    dir: Option<&Path>,
+    group_heading_vec: &[GroupHeading],
+    config: &Config,
+) {
+    use column::{
+        across_format, commas_format, single_column_format, vertical_format, zero_format,
+    };
+    use delimited::{csv_format, tsv_format};
+    use format_string::format_string_format;
+    use json::{json_format, ndjson_format};
+    use long::long_format;
 
     match config.output_format {
-        OutputFormat::SingleColumn => single_column_format(entrybuf_vec, config),
-        OutputFormat::Vertical => vertical_format(entrybuf_vec, config),
-        OutputFormat::Across => across_format(entrybuf_vec, config),
-        OutputFormat::Long => long_format(entrybuf_vec, config),
+        OutputFormat::SingleColumn => single_column_format(entrybuf_vec, group_heading_vec, config),
+        OutputFormat::Vertical => vertical_format(entrybuf_vec, group_heading_vec, config),
+        OutputFormat::Across => across_format(entrybuf_vec, group_heading_vec, config),
+        OutputFormat::Long => long_format(entrybuf_vec, group_heading_vec, config),
+        OutputFormat::Json => json_format(entrybuf_vec, dir),
+        OutputFormat::Ndjson => ndjson_format(entrybuf_vec, dir),
+        OutputFormat::Csv => csv_format(entrybuf_vec, config),
+        OutputFormat::Tsv => tsv_format(entrybuf_vec, config),
+        OutputFormat::FormatString => format_string_format(entrybuf_vec, config),
+        OutputFormat::Commas => commas_format(entrybuf_vec, config),
+        OutputFormat::Zero => zero_format(entrybuf_vec, config),
     }
 }
 
+/// Prints the `path:` header line before listing the contents of a directory.
+pub fn print_dir_header(path: &Path, is_first: bool, config: &Config) {
+    // The root of a tree already displays the directory path
+    if config.output_format.is_machine_readable() || config.tree {
+        return;
+    }
+
+    let path_name = quote_file_name(path.as_os_str(), config);
+    let line_terminator = config.output_format.line_terminator();
+    let mut buffer: Vec<u8> = Vec::with_capacity(path_name.bytes().len() + 8);
+    if !is_first {
+        buffer.push(line_terminator as u8);
+    }
+    if config.dired {
+        buffer.extend_from_slice(DIRED_INDENT.as_bytes());
+        dired::push_dir_name(buffer.len(), path_name.bytes().len());
+    }
+    buffer.extend_from_slice(path_name.bytes());
+    buffer.push(b':');
+    buffer.push(line_terminator as u8);
+
+    if config.dired {
+        dired::add_printed_len(buffer.len());
+    }
+    print_bytes(&buffer);
+}
+
+pub fn print_str(string: &str) {
+    print_bytes(string.as_bytes());
+}
+
+/// Prints bytes to stdout, such as file names that are not valid UTF-8.
+///
+/// In dired mode, callers count what they print with [`dired::add_printed_len`].
+pub fn print_bytes(bytes: &[u8]) {
+    io::stdout()
+        .lock()
+        .write_all(bytes)
+        .expect("failed printing to stdout");
+}
+
 pub fn print_total(entrybuf_vec: &[EntryBuf], config: &Config) {
+    if config.output_format.is_machine_readable() {
+        return;
+    }
+
     let total: u64 = entrybuf_vec
         .iter()
         .map(|entrybuf| entrybuf.allocated_size().unwrap_or(0))
         .sum();
 
-    println!("total {}", format_size(total, config).contents);
+    let indent = if config.dired { DIRED_INDENT } else { "" };
+    let total_line = format!(
+        "{}total {}{}",
+        indent,
+        format_size(total, config).contents,
+        config.output_format.line_terminator()
+    );
+    if config.dired {
+        dired::add_printed_len(total_line.len());
+    }
+    print_str(&total_line);
 }
@@ -0,0 +1,388 @@
+use std::ffi::OsStr;
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::config::{Config, QuotingStyle};
+use crate::locale::is_utf8_locale;
+use crate::output::{GridCell, GridCellExts};
+
+/// A char of a file name, or a byte of it that is not valid UTF-8.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NameUnit {
+    Char(char),
+    Byte(u8),
+}
+
+impl NameUnit {
+    /// Returns true for control characters and bytes that are not valid UTF-8.
+    fn is_unprintable(self) -> bool {
+        match self {
+            Self::Char(ch) => ch.is_control(),
+            Self::Byte(_) => true,
+        }
+    }
+
+    fn is(self, ch: char) -> bool {
+        self == Self::Char(ch)
+    }
+}
+
+/// Quotes a file name with the quoting style set by '--quoting-style', then replaces
+/// unprintable characters that are left with `?` if '--hide-control-chars' is set.
+///
+/// Bytes of the file name that are not valid UTF-8 are kept as is in the bytes
+/// of the returned cell, see [`GridCell::raw_contents`].
+pub fn quote_file_name(file_name: &OsStr, config: &Config) -> GridCell {
+    let name_units = name_units(file_name);
+    let mut quoted = QuotedName {
+        cell: GridCell::with_capacity(file_name.len() + 2),
+        hide_control_chars: config.hide_control_chars,
+    };
+
+    match config.quoting_style {
+        QuotingStyle::Literal => quoted.push_units(&name_units),
+        QuotingStyle::Locale => {
+            let (open_quote, close_quote) = if is_utf8_locale() {
+                ('‘', '’')
+            } else {
+                ('\'', '\'')
+            };
+            c_quote(&mut quoted, &name_units, Some((open_quote, close_quote)));
+        }
+        QuotingStyle::Shell => shell_quote(&mut quoted, &name_units, false, false),
+        QuotingStyle::ShellAlways => shell_quote(&mut quoted, &name_units, true, false),
+        QuotingStyle::ShellEscape => shell_quote(&mut quoted, &name_units, false, true),
+        QuotingStyle::ShellEscapeAlways => shell_quote(&mut quoted, &name_units, true, true),
+        QuotingStyle::C => c_quote(&mut quoted, &name_units, Some(('"', '"'))),
+        QuotingStyle::Escape => c_quote(&mut quoted, &name_units, None),
+    }
+
+    let mut cell = quoted.cell;
+    cell.width = UnicodeWidthStr::width(&*cell.contents);
+
+    cell
+}
+
+/// Splits a file name into chars, and bytes that are not valid UTF-8 on unix.
+#[cfg(unix)]
+fn name_units(file_name: &OsStr) -> Vec<NameUnit> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let mut bytes = file_name.as_bytes();
+    let mut name_units: Vec<NameUnit> = Vec::with_capacity(bytes.len());
+    loop {
+        match std::str::from_utf8(bytes) {
+            Ok(valid_str) => {
+                name_units.extend(valid_str.chars().map(NameUnit::Char));
+                return name_units;
+            }
+            Err(err) => {
+                let (valid_bytes, rest) = bytes.split_at(err.valid_up_to());
+                // SAFETY: the bytes up to `valid_up_to` are valid UTF-8
+                let valid_str = unsafe { std::str::from_utf8_unchecked(valid_bytes) };
+                name_units.extend(valid_str.chars().map(NameUnit::Char));
+
+                let num_invalid_bytes = err.error_len().unwrap_or(rest.len());
+                name_units.extend(
+                    rest[..num_invalid_bytes]
+                        .iter()
+                        .map(|byte| NameUnit::Byte(*byte)),
+                );
+                bytes = &rest[num_invalid_bytes..];
+            }
+        }
+    }
+}
+
+/// Splits a file name into chars, where unpaired surrogates are replaced with U+FFFD
+/// as only unix file names are made of bytes.
+#[cfg(not(unix))]
+fn name_units(file_name: &OsStr) -> Vec<NameUnit> {
+    file_name
+        .to_string_lossy()
+        .chars()
+        .map(NameUnit::Char)
+        .collect()
+}
+
+/// A quoted file name being built.
+struct QuotedName {
+    cell: GridCell,
+    hide_control_chars: bool,
+}
+
+impl QuotedName {
+    fn push_str(&mut self, string: &str) {
+        self.cell.push_str_with_width(string, 0);
+    }
+
+    fn push_char(&mut self, ch: char) {
+        self.cell
+            .push_str_with_width(ch.encode_utf8(&mut [0; 4]), 0);
+    }
+
+    /// Pushes a char or byte of the file name as is, unless it is unprintable
+    /// and '--hide-control-chars' is set.
+    fn push_unit(&mut self, name_unit: NameUnit) {
+        match name_unit {
+            _ if self.hide_control_chars && name_unit.is_unprintable() => self.push_char('?'),
+            NameUnit::Char(ch) => self.push_char(ch),
+            NameUnit::Byte(byte) => self.cell.push_raw_byte(byte),
+        }
+    }
+
+    fn push_units(&mut self, name_units: &[NameUnit]) {
+        for name_unit in name_units {
+            self.push_unit(*name_unit);
+        }
+    }
+
+    /// Pushes the C escape of a char, where bytes that are not valid UTF-8
+    /// are escaped as octal e.g. `\351`.
+    fn push_c_escape(&mut self, name_unit: NameUnit) {
+        match name_unit {
+            NameUnit::Byte(byte) => self.push_str(&format!("\\{:03o}", byte)),
+            NameUnit::Char('\x07') => self.push_str("\\a"),
+            NameUnit::Char('\x08') => self.push_str("\\b"),
+            NameUnit::Char('\x0c') => self.push_str("\\f"),
+            NameUnit::Char('\n') => self.push_str("\\n"),
+            NameUnit::Char('\r') => self.push_str("\\r"),
+            NameUnit::Char('\t') => self.push_str("\\t"),
+            NameUnit::Char('\x0b') => self.push_str("\\v"),
+            NameUnit::Char('\\') => self.push_str("\\\\"),
+            NameUnit::Char(ch) => self.push_str(&format!("\\{:03o}", ch as u32)),
+        }
+    }
+}
+
+/// Quotes a file name so that it can be pasted into a POSIX shell.
+///
+/// Names are only quoted when needed unless `always` is set. With `escape`,
+/// unprintable characters are written as `$'\n'` instead of being left as is.
+fn shell_quote(quoted: &mut QuotedName, name_units: &[NameUnit], always: bool, escape: bool) {
+    let contains = |chars: &[char]| {
+        name_units
+            .iter()
+            .any(|name_unit| chars.iter().any(|ch| name_unit.is(*ch)))
+    };
+    let needs_quotes = always
+        || name_units.is_empty()
+        || name_units == [NameUnit::Char('{')]
+        || name_units == [NameUnit::Char('}')]
+        || name_units[0].is('#')
+        || name_units[0].is('~')
+        || name_units.iter().any(|name_unit| {
+            name_unit.is_unprintable()
+                || matches!(name_unit, NameUnit::Char(ch) if is_shell_special_char(*ch))
+        });
+    if !needs_quotes {
+        quoted.push_units(name_units);
+        return;
+    }
+
+    let has_escapes = escape
+        && name_units
+            .iter()
+            .any(|name_unit| name_unit.is_unprintable());
+    if !has_escapes && contains(&['\'']) && !contains(&['"', '$', '`', '\\', '!']) {
+        quoted.push_char('"');
+        quoted.push_units(name_units);
+        quoted.push_char('"');
+        return;
+    }
+
+    let mut is_in_escape_quotes = false;
+    quoted.push_char('\'');
+    for name_unit in name_units {
+        if escape && name_unit.is_unprintable() {
+            if !is_in_escape_quotes {
+                quoted.push_str("'$'");
+                is_in_escape_quotes = true;
+            }
+            quoted.push_c_escape(*name_unit);
+        } else {
+            if is_in_escape_quotes {
+                quoted.push_str("''");
+                is_in_escape_quotes = false;
+            }
+            if name_unit.is('\'') {
+                quoted.push_str("'\\''");
+            } else {
+                quoted.push_unit(*name_unit);
+            }
+        }
+    }
+    quoted.push_char('\'');
+}
+
+fn is_shell_special_char(ch: char) -> bool {
+    matches!(
+        ch,
+        ' ' | '!'
+            | '"'
+            | '$'
+            | '&'
+            | '\''
+            | '('
+            | ')'
+            | '*'
+            | ';'
+            | '<'
+            | '>'
+            | '?'
+            | '['
+            | '\\'
+            | ']'
+            | '^'
+            | '`'
+            | '|'
+    )
+}
+
+/// Escapes backslashes and unprintable characters like a C string literal, surrounded by
+/// `quotes` if any. Without quotes, spaces are escaped as `\ ` like GNU's `-b`.
+fn c_quote(quoted: &mut QuotedName, name_units: &[NameUnit], quotes: Option<(char, char)>) {
+    if let Some((open_quote, _)) = quotes {
+        quoted.push_char(open_quote);
+    }
+    for name_unit in name_units {
+        match quotes {
+            _ if name_unit.is('\\') || name_unit.is_unprintable() => {
+                quoted.push_c_escape(*name_unit)
+            }
+            Some((_, close_quote)) if name_unit.is(close_quote) => {
+                quoted.push_char('\\');
+                quoted.push_char(close_quote);
+            }
+            None if name_unit.is(' ') => quoted.push_str("\\ "),
+            _ => quoted.push_unit(*name_unit),
+        }
+    }
+    if let Some((_, close_quote)) = quotes {
+        quoted.push_char(close_quote);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quote_with(file_name: &str, quoting_style: QuotingStyle) -> String {
+        let config = Config {
+            quoting_style: quoting_style,
+            ..Default::default()
+        };
+
+        quote_file_name(OsStr::new(file_name), &config)
+            .contents
+            .to_string()
+    }
+
+    #[test]
+    fn test_quote_file_name_shell() {
+        assert_eq!(quote_with("main.rs", QuotingStyle::Shell), "main.rs");
+        assert_eq!(quote_with("a b", QuotingStyle::Shell), "'a b'");
+        assert_eq!(quote_with("it's", QuotingStyle::Shell), "\"it's\"");
+        assert_eq!(quote_with("it's $x", QuotingStyle::Shell), "'it'\\''s $x'");
+        assert_eq!(quote_with("~home", QuotingStyle::Shell), "'~home'");
+        assert_eq!(quote_with("a~b", QuotingStyle::Shell), "a~b");
+        assert_eq!(
+            quote_with("main.rs", QuotingStyle::ShellAlways),
+            "'main.rs'"
+        );
+    }
+
+    #[test]
+    fn test_quote_file_name_shell_escape() {
+        assert_eq!(
+            quote_with("a\nb", QuotingStyle::ShellEscape),
+            "'a'$'\\n''b'"
+        );
+        assert_eq!(
+            quote_with("\x1b]0;x\x07", QuotingStyle::ShellEscape),
+            "''$'\\033'']0;x'$'\\a'"
+        );
+        assert_eq!(
+            quote_with("a\t\tb", QuotingStyle::ShellEscape),
+            "'a'$'\\t\\t''b'"
+        );
+        assert_eq!(quote_with("main.rs", QuotingStyle::ShellEscape), "main.rs");
+        assert_eq!(
+            quote_with("main.rs", QuotingStyle::ShellEscapeAlways),
+            "'main.rs'"
+        );
+    }
+
+    #[test]
+    fn test_quote_file_name_c_and_escape() {
+        assert_eq!(
+            quote_with("a \"b\"\n", QuotingStyle::C),
+            "\"a \\\"b\\\"\\n\""
+        );
+        assert_eq!(
+            quote_with("a b\\\x7f", QuotingStyle::Escape),
+            "a\\ b\\\\\\177"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_quote_file_name_latin1() {
+        use std::os::unix::ffi::OsStrExt;
+
+        // "café" with 'é' encoded in Latin-1
+        let file_name = OsStr::from_bytes(b"caf\xe9");
+        let quote_bytes_with = |quoting_style: QuotingStyle| {
+            let config = Config {
+                quoting_style: quoting_style,
+                ..Default::default()
+            };
+            quote_file_name(file_name, &config).bytes().to_vec()
+        };
+        assert_eq!(
+            quote_bytes_with(QuotingStyle::ShellEscape),
+            b"'caf'$'\\351'"
+        );
+        assert_eq!(quote_bytes_with(QuotingStyle::C), b"\"caf\\351\"");
+        assert_eq!(quote_bytes_with(QuotingStyle::Shell), b"'caf\xe9'");
+
+        let cell = quote_file_name(file_name, &Config::default());
+        assert_eq!(cell.bytes(), b"caf\xe9");
+        assert_eq!(&*cell.contents, "caf\u{fffd}");
+        assert_eq!(cell.width, 4);
+
+        let config = Config {
+            hide_control_chars: true,
+            ..Default::default()
+        };
+        assert_eq!(quote_file_name(file_name, &config).bytes(), b"caf?");
+    }
+
+    #[test]
+    fn test_quote_file_name_utf8_at_end_of_unicode() {
+        // Valid UTF-8 names are written as is, even with chars in private use planes
+        let file_name = OsStr::new("a\u{10ffff}b");
+        let cell = quote_file_name(file_name, &Config::default());
+        assert_eq!(cell.bytes(), b"a\xf4\x8f\xbf\xbfb");
+        assert_eq!(cell.raw_contents, None);
+    }
+
+    #[test]
+    fn test_quote_file_name_hide_control_chars() {
+        let mut config = Config {
+            hide_control_chars: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            quote_file_name(OsStr::new("a\x1b[31mb\n"), &config).contents,
+            "a?[31mb?"
+        );
+
+        // Control characters are already escaped by escaping quoting styles
+        config.quoting_style = QuotingStyle::ShellEscape;
+        assert_eq!(
+            quote_file_name(OsStr::new("a\nb"), &config).contents,
+            "'a'$'\\n''b'"
+        );
+    }
+}
@@ -1,47 +1,700 @@
 use std::cmp::Ordering;
 
-use crate::config::{Config, SortingOrder};
+use compact_str::{format_compact, CompactString};
+use unicode_width::UnicodeWidthStr;
+
+use crate::config::{Collation, Config, GroupBy, SortKey, SortingOrder, TimestampUsed};
 use crate::entry::EntryBuf;
+use crate::utils::os_str_bytes;
 
-pub fn sort_entrybuf_vec(entrybuf_vec: &mut Vec<EntryBuf>, config: &Config) {
+pub fn sort_entrybuf_vec(entrybuf_vec: &mut [EntryBuf], config: &Config) {
     if entrybuf_vec.len() < 2 {
         return;
     }
 
-    match config.sorting_order {
-        SortingOrder::FileName => {
-            entrybuf_vec.sort_by(file_name_compare);
-            log::debug!("Sorted by file name");
+    if config
+        .sort_keys
+        .iter()
+        .all(|sort_key| sort_key.sorting_order == SortingOrder::None)
+    {
+        log::debug!("Not sorted");
+    } else {
+        sort_by_keys(entrybuf_vec, config);
+    }
+
+    // A stable sort by group keeps the sorting order within each group,
+    // while groups themselves are never reversed
+    if let Some(group_by) = config.group_by {
+        entrybuf_vec.sort_by_cached_key(|entrybuf| EntryGroup::new(entrybuf, group_by));
+    }
+}
+
+fn sort_by_keys(entrybuf_vec: &mut [EntryBuf], config: &Config) {
+    let sort_keys = &config.sort_keys;
+
+    // Values that are expensive to get are only computed once per entry instead of
+    // on every comparison, thus entries are moved into a vec alongside them.
+    let mut cached_entrybuf_vec: Vec<(SortCache, EntryBuf)> = entrybuf_vec
+        .iter_mut()
+        .map(|entrybuf| (SortCache::new(entrybuf, config), std::mem::take(entrybuf)))
+        .collect();
+
+    cached_entrybuf_vec.sort_by(|(cache_1, entrybuf_1), (cache_2, entrybuf_2)| {
+        sort_keys
+            .iter()
+            .fold(Ordering::Equal, |ordering, sort_key| {
+                ordering.then_with(|| {
+                    sort_key_compare(
+                        sort_key,
+                        (cache_1, entrybuf_1),
+                        (cache_2, entrybuf_2),
+                        config,
+                    )
+                })
+            })
+            .then_with(|| file_name_compare(entrybuf_1, entrybuf_2, config))
+    });
+    log::debug!("Sorted by {:?}", sort_keys);
+
+    for (entrybuf, (_, sorted_entrybuf)) in entrybuf_vec.iter_mut().zip(cached_entrybuf_vec) {
+        *entrybuf = sorted_entrybuf;
+    }
+
+    if config.reverse {
+        entrybuf_vec.reverse();
+    }
+}
+
+/// The group an entry is listed in when grouping with '--group-directories-first'
+/// or '--group-by', ordered by `rank` then `name`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct EntryGroup {
+    rank: u8,
+    name: CompactString,
+}
+
+impl EntryGroup {
+    /// Returns the group of an entry. Symlinks to directories are grouped as
+    /// directories when dereferencing as their metadata is of the target.
+    pub fn new(entrybuf: &EntryBuf, group_by: GroupBy) -> Self {
+        match group_by {
+            GroupBy::Directories => {
+                let is_dir = entrybuf
+                    .metadata()
+                    .map(|metadata| metadata.is_dir())
+                    .unwrap_or(false);
+
+                Self::with_rank(!is_dir as u8)
+            }
+            GroupBy::FileType => Self::with_rank(file_type_rank(entrybuf)),
+            GroupBy::Extension => match extension(entrybuf.file_name()) {
+                "" => Self::with_rank(0),
+                extension => Self {
+                    rank: 1,
+                    name: CompactString::from(extension),
+                },
+            },
+            GroupBy::Owner => match entrybuf.owner_name() {
+                Some(owner_name) => Self {
+                    rank: 0,
+                    name: owner_name,
+                },
+                None => Self::with_rank(1),
+            },
         }
-        SortingOrder::Size => {
-            entrybuf_vec.sort_by(size_compare);
-            log::debug!("Sorted by size");
+    }
+
+    fn with_rank(rank: u8) -> Self {
+        Self {
+            rank: rank,
+            name: CompactString::default(),
         }
-        SortingOrder::Timestamp => {
-            entrybuf_vec.sort_by(timestamp_compare);
-            log::debug!("Sorted by time");
+    }
+
+    /// Returns the section heading printed before the entries of this group by '--group-headings'.
+    pub fn heading(&self, group_by: GroupBy) -> CompactString {
+        match (group_by, self.rank) {
+            (GroupBy::Directories, 0) | (GroupBy::FileType, 0) => "Directories".into(),
+            (GroupBy::Directories, _) | (GroupBy::FileType, 1) => "Files".into(),
+            (GroupBy::FileType, 2) => "Symlinks".into(),
+            (GroupBy::FileType, 3) => "Other".into(),
+            (GroupBy::FileType, _) => "Unknown".into(),
+            (GroupBy::Extension, 0) => "No extension".into(),
+            (GroupBy::Extension, _) => format_compact!(".{}", self.name),
+            (GroupBy::Owner, 0) => self.name.clone(),
+            (GroupBy::Owner, _) => "Unknown owner".into(),
         }
     }
+}
 
-    if config.reverse {
-        entrybuf_vec.reverse();
+/// Values of an entry used for sorting that are expensive to get.
+#[derive(Default)]
+struct SortCache {
+    extension_key: Option<Vec<u8>>,
+    ino: Option<u128>,
+    owner_name: Option<CompactString>,
+    group_name: Option<CompactString>,
+}
+
+impl SortCache {
+    fn new(entrybuf: &EntryBuf, config: &Config) -> Self {
+        let mut sort_cache = Self::default();
+
+        for sort_key in &config.sort_keys {
+            match sort_key.sorting_order {
+                SortingOrder::Extension => {
+                    sort_cache.extension_key = Some(collation_key(
+                        extension(entrybuf.file_name()).as_bytes(),
+                        config,
+                    ))
+                }
+                SortingOrder::Inode => sort_cache.ino = entrybuf.ino().map(u128::from),
+                SortingOrder::Owner => sort_cache.owner_name = entrybuf.owner_name(),
+                SortingOrder::Group => sort_cache.group_name = entrybuf.group_name(),
+                _ => (),
+            }
+        }
+
+        sort_cache
     }
 }
 
-fn file_name_compare(entrybuf_1: &EntryBuf, entrybuf_2: &EntryBuf) -> Ordering {
-    entrybuf_1.file_name_key().cmp(entrybuf_2.file_name_key())
+/// Compares two entries by a single sort key, without breaking ties.
+fn sort_key_compare(
+    sort_key: &SortKey,
+    (cache_1, entrybuf_1): (&SortCache, &EntryBuf),
+    (cache_2, entrybuf_2): (&SortCache, &EntryBuf),
+    config: &Config,
+) -> Ordering {
+    let ordering = match sort_key.sorting_order {
+        SortingOrder::None => Ordering::Equal,
+        SortingOrder::FileName => file_name_compare(entrybuf_1, entrybuf_2, config),
+        SortingOrder::Size => entrybuf_2.size().cmp(&entrybuf_1.size()),
+        SortingOrder::Timestamp => entrybuf_2.timestamp().cmp(&entrybuf_1.timestamp()),
+        SortingOrder::Extension => cache_1.extension_key.cmp(&cache_2.extension_key),
+        SortingOrder::Version => natural_compare(
+            &os_str_bytes(entrybuf_1.raw_file_name()),
+            &os_str_bytes(entrybuf_2.raw_file_name()),
+        ),
+        SortingOrder::Width => UnicodeWidthStr::width(entrybuf_1.file_name())
+            .cmp(&UnicodeWidthStr::width(entrybuf_2.file_name())),
+        SortingOrder::Inode => cache_1.ino.cmp(&cache_2.ino),
+        SortingOrder::Nlink => entrybuf_2.nlink().cmp(&entrybuf_1.nlink()),
+        SortingOrder::Owner => cache_1.owner_name.cmp(&cache_2.owner_name),
+        SortingOrder::Group => cache_1.group_name.cmp(&cache_2.group_name),
+        SortingOrder::Created => entrybuf_2
+            .timestamp_of(&TimestampUsed::Created)
+            .cmp(&entrybuf_1.timestamp_of(&TimestampUsed::Created)),
+        SortingOrder::FileType => file_type_rank(entrybuf_1).cmp(&file_type_rank(entrybuf_2)),
+    };
+
+    if sort_key.reverse {
+        ordering.reverse()
+    } else {
+        ordering
+    }
+}
+
+fn file_name_compare(entrybuf_1: &EntryBuf, entrybuf_2: &EntryBuf, config: &Config) -> Ordering {
+    match config.collation {
+        Collation::Natural => {
+            natural_compare(entrybuf_1.file_name_key(), entrybuf_2.file_name_key())
+        }
+        _ => entrybuf_1.file_name_key().cmp(entrybuf_2.file_name_key()),
+    }
+}
+
+/// Returns the key the bytes of a file name are compared by with the collation set by
+/// '--collate', without the leading dots if '--ignore-leading-dots' is set.
+pub fn collation_key(file_name: &[u8], config: &Config) -> Vec<u8> {
+    let file_name = if config.ignore_leading_dots {
+        let num_dots = file_name.iter().take_while(|byte| **byte == b'.').count();
+        &file_name[num_dots..]
+    } else {
+        file_name
+    };
+
+    match config.collation {
+        Collation::Bytes | Collation::Natural => file_name.to_vec(),
+        Collation::CaseInsensitive => match std::str::from_utf8(file_name) {
+            Ok(file_name) => file_name.to_lowercase().into_bytes(),
+            Err(_) => file_name.to_ascii_lowercase(),
+        },
+        Collation::Locale => locale_collation_key(file_name),
+    }
 }
 
-fn size_compare(entrybuf_1: &EntryBuf, entrybuf_2: &EntryBuf) -> Ordering {
-    entrybuf_2
-        .size()
-        .cmp(&entrybuf_1.size())
-        .then(file_name_compare(entrybuf_1, entrybuf_2))
+/// Returns a key that orders file names like the collation of UTF-8 locales.
+///
+/// The key is made of three levels separated by NUL, which cannot be part of a file name:
+/// the lowercased alphanumeric characters, then the case of each of them with lowercase
+/// first, then the bytes of the file name itself so that names differing only in
+/// punctuation are still ordered.
+fn locale_collation_key(file_name: &[u8]) -> Vec<u8> {
+    let file_name_str = String::from_utf8_lossy(file_name);
+    let mut key = String::with_capacity(file_name.len() * 2 + 2);
+    for ch in file_name_str.chars().filter(|ch| ch.is_alphanumeric()) {
+        key.extend(ch.to_lowercase());
+    }
+    key.push('\0');
+    for ch in file_name_str.chars().filter(|ch| ch.is_alphanumeric()) {
+        key.push(if ch.is_uppercase() { '1' } else { '0' });
+    }
+    key.push('\0');
+
+    let mut key = key.into_bytes();
+    key.extend_from_slice(file_name);
+
+    key
 }
 
-fn timestamp_compare(entrybuf_1: &EntryBuf, entrybuf_2: &EntryBuf) -> Ordering {
-    entrybuf_2
-        .timestamp()
-        .cmp(&entrybuf_1.timestamp())
-        .then(file_name_compare(entrybuf_1, entrybuf_2))
+/// Returns the rank of the file type of an entry, where directories are ranked first
+/// and entries without metadata are ranked last.
+fn file_type_rank(entrybuf: &EntryBuf) -> u8 {
+    match entrybuf.metadata().map(|metadata| metadata.file_type()) {
+        Some(file_type) if file_type.is_dir() => 0,
+        Some(file_type) if file_type.is_file() => 1,
+        Some(file_type) if file_type.is_symlink() => 2,
+        Some(_) => 3,
+        None => 4,
+    }
+}
+
+/// Returns the extension of a file name, which is empty if the file name has no '.'
+/// so that entries without an extension are sorted first.
+fn extension(file_name: &str) -> &str {
+    match file_name.rfind('.') {
+        Some(index) => &file_name[index + 1..],
+        None => "",
+    }
+}
+
+/// Compares two byte strings with runs of digits compared by their numeric value,
+/// thus `file2` is ordered before `file10`.
+pub fn natural_compare(mut bytes_1: &[u8], mut bytes_2: &[u8]) -> Ordering {
+    loop {
+        match (bytes_1.first(), bytes_2.first()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(byte_1), Some(byte_2)) if byte_1.is_ascii_digit() && byte_2.is_ascii_digit() => {
+                let (digits_1, rest_1) = split_digits(bytes_1);
+                let (digits_2, rest_2) = split_digits(bytes_2);
+                let number_1 = trim_leading_zeros(digits_1);
+                let number_2 = trim_leading_zeros(digits_2);
+
+                // A number with more digits is larger, else compare digit by digit
+                let ordering = number_1
+                    .len()
+                    .cmp(&number_2.len())
+                    .then(number_1.cmp(number_2))
+                    .then(digits_1.len().cmp(&digits_2.len()));
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+
+                bytes_1 = rest_1;
+                bytes_2 = rest_2;
+            }
+            (Some(byte_1), Some(byte_2)) => {
+                let ordering = byte_1.cmp(byte_2);
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+
+                bytes_1 = &bytes_1[1..];
+                bytes_2 = &bytes_2[1..];
+            }
+        }
+    }
+}
+
+fn split_digits(bytes: &[u8]) -> (&[u8], &[u8]) {
+    let num_digits = bytes
+        .iter()
+        .position(|byte| !byte.is_ascii_digit())
+        .unwrap_or(bytes.len());
+
+    bytes.split_at(num_digits)
+}
+
+fn trim_leading_zeros(digits: &[u8]) -> &[u8] {
+    let num_zeros = digits
+        .iter()
+        .position(|byte| *byte != b'0')
+        .unwrap_or(digits.len());
+
+    &digits[num_zeros..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_natural_compare() {
+        assert_eq!(natural_compare(b"file2", b"file10"), Ordering::Less);
+        assert_eq!(natural_compare(b"file10", b"file2"), Ordering::Greater);
+        assert_eq!(natural_compare(b"v1.9.0", b"v1.10.0"), Ordering::Less);
+        assert_eq!(natural_compare(b"file", b"file1"), Ordering::Less);
+        assert_eq!(natural_compare(b"file01", b"file1"), Ordering::Greater);
+        assert_eq!(natural_compare(b"file1", b"file1"), Ordering::Equal);
+        assert_eq!(natural_compare(b"a2", b"b1"), Ordering::Less);
+    }
+
+    /// Returns entries of the files and directories (names ending with '/')
+    /// created in a temporary directory, in the order of `names`.
+    fn entrybufs_from(
+        tmpdir: &tempfile::TempDir,
+        names: &[&str],
+        config: &Config,
+    ) -> Vec<EntryBuf> {
+        names
+            .iter()
+            .map(|name| {
+                let file_name = name.trim_end_matches('/');
+                let path = tmpdir.path().join(file_name);
+                if name.ends_with('/') {
+                    std::fs::create_dir(&path).unwrap();
+                } else {
+                    std::fs::write(&path, name).unwrap();
+                }
+                EntryBuf::from_named_path(file_name, &path, config)
+            })
+            .collect()
+    }
+
+    fn sorted_file_names(entrybuf_vec: &mut [EntryBuf], config: &Config) -> Vec<String> {
+        sort_entrybuf_vec(entrybuf_vec, config);
+        entrybuf_vec
+            .iter()
+            .map(|entrybuf| entrybuf.file_name().to_string())
+            .collect()
+    }
+
+    /// Asserts that entries are sorted into `expected` by `sorting_order`,
+    /// and into the reverse of `expected` with '-r'.
+    fn assert_sorted_by(
+        entrybuf_vec: &mut [EntryBuf],
+        sorting_order: SortingOrder,
+        expected: &[&str],
+    ) {
+        let mut config = Config {
+            sort_keys: vec![SortKey::new(sorting_order)],
+            ..Default::default()
+        };
+        assert_eq!(sorted_file_names(entrybuf_vec, &config), expected);
+
+        config.reverse = true;
+        let reversed: Vec<&str> = expected.iter().rev().copied().collect();
+        assert_eq!(sorted_file_names(entrybuf_vec, &config), reversed);
+    }
+
+    #[test]
+    fn test_sort_entrybuf_vec_extension() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let names = ["b.txt", "a.rs", "c", "a.txt", "d.rs"];
+        let mut entrybuf_vec = entrybufs_from(&tmpdir, &names, &Config::default());
+
+        assert_sorted_by(
+            &mut entrybuf_vec,
+            SortingOrder::Extension,
+            &["c", "a.rs", "d.rs", "a.txt", "b.txt"],
+        );
+    }
+
+    #[test]
+    fn test_sort_entrybuf_vec_version() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let names = ["file10", "file2", "file1a", "file1"];
+        let mut entrybuf_vec = entrybufs_from(&tmpdir, &names, &Config::default());
+
+        assert_sorted_by(
+            &mut entrybuf_vec,
+            SortingOrder::Version,
+            &["file1", "file1a", "file2", "file10"],
+        );
+    }
+
+    #[test]
+    fn test_sort_entrybuf_vec_width() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let names = ["ccc", "a", "dd", "bb"];
+        let mut entrybuf_vec = entrybufs_from(&tmpdir, &names, &Config::default());
+
+        assert_sorted_by(
+            &mut entrybuf_vec,
+            SortingOrder::Width,
+            &["a", "bb", "dd", "ccc"],
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sort_entrybuf_vec_inode() {
+        use std::os::unix::fs::MetadataExt;
+
+        let tmpdir = tempfile::tempdir().unwrap();
+        let names = ["a", "b", "c", "d"];
+        let mut entrybuf_vec = entrybufs_from(&tmpdir, &names, &Config::default());
+
+        let mut expected = names.to_vec();
+        expected.sort_by_key(|name| tmpdir.path().join(name).metadata().unwrap().ino());
+        assert_sorted_by(&mut entrybuf_vec, SortingOrder::Inode, &expected);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_sort_entrybuf_vec_nlink() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        for name in ["a", "b", "c"] {
+            std::fs::write(tmpdir.path().join(name), name).unwrap();
+        }
+        std::fs::hard_link(tmpdir.path().join("c"), tmpdir.path().join("d")).unwrap();
+        let mut entrybuf_vec: Vec<EntryBuf> = ["a", "b", "c", "d"]
+            .iter()
+            .map(|name| {
+                EntryBuf::from_named_path(name, &tmpdir.path().join(name), &Config::default())
+            })
+            .collect();
+
+        // Entries with the most links are listed first
+        assert_sorted_by(
+            &mut entrybuf_vec,
+            SortingOrder::Nlink,
+            &["c", "d", "a", "b"],
+        );
+    }
+
+    #[test]
+    fn test_sort_entrybuf_vec_owner_and_group() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let names = ["c", "a", "b"];
+        let mut entrybuf_vec = entrybufs_from(&tmpdir, &names, &Config::default());
+
+        // Entries of the same owner and group are sorted by name
+        assert_sorted_by(&mut entrybuf_vec, SortingOrder::Owner, &["a", "b", "c"]);
+        assert_sorted_by(&mut entrybuf_vec, SortingOrder::Group, &["a", "b", "c"]);
+
+        let config = Config::default();
+        let entrybuf = EntryBuf::default();
+        let alice = SortCache {
+            owner_name: Some(CompactString::from("alice")),
+            group_name: Some(CompactString::from("staff")),
+            ..Default::default()
+        };
+        let bob = SortCache {
+            owner_name: Some(CompactString::from("bob")),
+            group_name: Some(CompactString::from("admin")),
+            ..Default::default()
+        };
+        assert_eq!(
+            sort_key_compare(
+                &SortKey::new(SortingOrder::Owner),
+                (&alice, &entrybuf),
+                (&bob, &entrybuf),
+                &config
+            ),
+            Ordering::Less
+        );
+        assert_eq!(
+            sort_key_compare(
+                &SortKey::new(SortingOrder::Group),
+                (&alice, &entrybuf),
+                (&bob, &entrybuf),
+                &config
+            ),
+            Ordering::Greater
+        );
+    }
+
+    #[test]
+    fn test_sort_entrybuf_vec_created() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let mut entrybuf_vec: Vec<EntryBuf> = Vec::new();
+        for name in ["b", "c", "a"] {
+            entrybuf_vec.extend(entrybufs_from(&tmpdir, &[name], &Config::default()));
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+
+        // Newest entries are listed first, or by name if the creation time is unsupported
+        let is_supported = tmpdir
+            .path()
+            .join("a")
+            .metadata()
+            .unwrap()
+            .created()
+            .is_ok();
+        let expected = if is_supported {
+            ["a", "c", "b"]
+        } else {
+            ["a", "b", "c"]
+        };
+        assert_sorted_by(&mut entrybuf_vec, SortingOrder::Created, &expected);
+    }
+
+    #[test]
+    fn test_sort_key_parse_list() {
+        assert_eq!(
+            SortKey::parse_list("type,ext,-size,name").unwrap(),
+            vec![
+                SortKey::new(SortingOrder::FileType),
+                SortKey::new(SortingOrder::Extension),
+                SortKey {
+                    sorting_order: SortingOrder::Size,
+                    reverse: true,
+                },
+                SortKey::new(SortingOrder::FileName),
+            ]
+        );
+        assert!(SortKey::parse_list("type,,name").is_err());
+        assert!(SortKey::parse_list("--size").is_err());
+        assert!(SortKey::parse_list("colour").is_err());
+    }
+
+    #[test]
+    fn test_sort_entrybuf_vec_file_type() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let names = ["f", "e/", "c", "d/"];
+        let mut entrybuf_vec = entrybufs_from(&tmpdir, &names, &Config::default());
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink("f", tmpdir.path().join("a")).unwrap();
+            entrybuf_vec.push(EntryBuf::from_named_path(
+                "a",
+                &tmpdir.path().join("a"),
+                &Config::default(),
+            ));
+        }
+
+        let mut expected = vec!["d", "e", "c", "f"];
+        if cfg!(unix) {
+            expected.push("a");
+        }
+        assert_sorted_by(&mut entrybuf_vec, SortingOrder::FileType, &expected);
+    }
+
+    #[test]
+    fn test_entry_group_heading() {
+        assert_eq!(
+            EntryGroup::with_rank(0).heading(GroupBy::Directories),
+            "Directories"
+        );
+        assert_eq!(
+            EntryGroup::with_rank(1).heading(GroupBy::Directories),
+            "Files"
+        );
+        assert_eq!(
+            EntryGroup::with_rank(2).heading(GroupBy::FileType),
+            "Symlinks"
+        );
+        assert_eq!(
+            EntryGroup::with_rank(0).heading(GroupBy::Extension),
+            "No extension"
+        );
+        assert_eq!(
+            EntryGroup {
+                rank: 1,
+                name: CompactString::from("rs"),
+            }
+            .heading(GroupBy::Extension),
+            ".rs"
+        );
+    }
+
+    #[test]
+    fn test_entry_group_ordering() {
+        let no_extension = EntryGroup::with_rank(0);
+        let rs = EntryGroup {
+            rank: 1,
+            name: CompactString::from("rs"),
+        };
+        let txt = EntryGroup {
+            rank: 1,
+            name: CompactString::from("txt"),
+        };
+
+        assert!(no_extension < rs);
+        assert!(rs < txt);
+    }
+
+    #[test]
+    fn test_sort_entrybuf_vec_unsorted_group_directories_first() {
+        let tmpdir = tempfile::tempdir().unwrap();
+        let config = Config {
+            sort_keys: vec![SortKey::new(SortingOrder::None)],
+            group_by: Some(GroupBy::Directories),
+            ..Default::default()
+        };
+        let mut entrybuf_vec = entrybufs_from(&tmpdir, &["b", "d/", "a", "c/"], &config);
+
+        // Entries are grouped while keeping their order within each group
+        assert_eq!(
+            sorted_file_names(&mut entrybuf_vec, &config),
+            ["d", "c", "b", "a"]
+        );
+    }
+
+    #[test]
+    fn test_collation_key() {
+        let mut names = vec!["b", "_a", ".c", "B", "a", "Makefile", "main.rs", "10", "9"];
+        let mut config = Config {
+            collation: Collation::Bytes,
+            ..Default::default()
+        };
+        names.sort_by_key(|name| collation_key(name.as_bytes(), &config));
+        assert_eq!(
+            names,
+            vec![".c", "10", "9", "B", "Makefile", "_a", "a", "b", "main.rs"]
+        );
+
+        config.collation = Collation::Locale;
+        names.sort_by_key(|name| collation_key(name.as_bytes(), &config));
+        assert_eq!(
+            names,
+            vec!["10", "9", "_a", "a", "b", "B", ".c", "main.rs", "Makefile"]
+        );
+
+        config.collation = Collation::CaseInsensitive;
+        config.ignore_leading_dots = true;
+        names.sort_by_key(|name| collation_key(name.as_bytes(), &config));
+        assert_eq!(
+            names,
+            vec!["10", "9", "_a", "a", "b", "B", ".c", "main.rs", "Makefile"]
+        );
+    }
+
+    #[test]
+    fn test_collation_key_latin1() {
+        // "café", "cafe" and "CAFÉ" with 'é' and 'É' encoded in Latin-1
+        let mut names: Vec<&[u8]> = vec![b"caf\xe9", b"cafe", b"CAF\xc9", b"caff"];
+        let mut config = Config {
+            collation: Collation::Bytes,
+            ..Default::default()
+        };
+        names.sort_by_key(|name| collation_key(name, &config));
+        assert_eq!(
+            names,
+            vec![&b"CAF\xc9"[..], &b"cafe"[..], &b"caff"[..], &b"caf\xe9"[..]]
+        );
+
+        config.collation = Collation::CaseInsensitive;
+        assert_eq!(collation_key(b"CAF\xc9", &config), b"caf\xc9");
+
+        // Invalid bytes are kept as the last level of the key
+        config.collation = Collation::Locale;
+        assert!(collation_key(b"caf\xe9", &config).ends_with(b"\0caf\xe9"));
+        assert_ne!(
+            collation_key(b"caf\xe9", &config),
+            collation_key(b"caf\xe8", &config)
+        );
+    }
+
+    #[test]
+    fn test_extension() {
+        assert_eq!(extension("main.rs"), "rs");
+        assert_eq!(extension("archive.tar.gz"), "gz");
+        assert_eq!(extension("makefile"), "");
+        assert_eq!(extension(".bashrc"), "bashrc");
+    }
 }
@@ -0,0 +1,66 @@
+use compact_str::CompactString;
+
+use crate::config::Config;
+use crate::entry::EntryBuf;
+use crate::output::column::single_column_format_with_name_prefixes;
+use crate::output::long::long_format_with_name_prefixes;
+use crate::output::{GridCell, GridCellExts};
+
+/// Prints entries as a tree, where each file name is prefixed with
+/// the branches at the same index in `prefix_vec`.
+///
+/// Entries are expected to already be in tree order i.e. each directory
+/// is directly followed by its (sorted) contents.
+pub fn tree_format(entrybuf_vec: &[EntryBuf], prefix_vec: &[CompactString], config: &Config) {
+    if config.output_format.is_long() {
+        long_format_with_name_prefixes(entrybuf_vec, prefix_vec, config);
+    } else {
+        single_column_format_with_name_prefixes(entrybuf_vec, prefix_vec, config);
+    }
+}
+
+pub fn prefixed_file_name_cell(
+    entrybuf: &EntryBuf,
+    prefix: Option<&CompactString>,
+    config: &Config,
+) -> GridCell {
+    match prefix {
+        Some(prefix) if !prefix.is_empty() => {
+            let mut cell = GridCell::with_capacity(prefix.len() + 32);
+            cell.push_str(prefix);
+            cell.append(entrybuf.file_name_cell(config));
+
+            cell
+        }
+        _ => entrybuf.file_name_cell(config),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::Path;
+
+    #[test]
+    fn test_prefixed_file_name_cell() {
+        let config = Config::default();
+        let entrybuf = EntryBuf::from_named_path("main.rs", Path::new("main.rs"), &config);
+        let prefix = CompactString::new("│   └── ");
+
+        let cell = prefixed_file_name_cell(&entrybuf, Some(&prefix), &config);
+        assert_eq!(cell.contents, "│   └── main.rs");
+        assert_eq!(cell.width, 15);
+    }
+
+    #[test]
+    fn test_prefixed_file_name_cell_no_prefix() {
+        let config = Config::default();
+        let entrybuf = EntryBuf::from_named_path("main.rs", Path::new("main.rs"), &config);
+
+        assert_eq!(
+            prefixed_file_name_cell(&entrybuf, None, &config),
+            entrybuf.file_name_cell(&config)
+        );
+    }
+}
@@ -33,6 +33,7 @@ pub struct ThemeConfig {
     #[cfg(windows)]
     system: Option<CompactString>,
     hidden: Option<CompactString>,
+    header: Option<CompactString>,
 }
 
 impl ThemeConfig {
@@ -73,6 +74,8 @@ impl ThemeConfig {
 
     theme_config_get_style_impl!(hidden, hidden_style, "hidden attribute.");
 
+    theme_config_get_style_impl!(header, header_style, "header.");
+
     pub fn with_default_colors() -> Self {
         Self {
             inode: Some(CompactString::new_inline("32;1")),
@@ -96,6 +99,7 @@ impl ThemeConfig {
             #[cfg(windows)]
             system: Some(CompactString::new_inline("40;33;01")),
             hidden: Some(CompactString::new_inline("35")),
+            header: Some(CompactString::new_inline("4")),
         }
     }
 }
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+use std::ffi::OsStr;
 use std::io;
 use std::time::SystemTime;
 
@@ -22,13 +24,92 @@ impl HasMaskSetExt for u32 {
     }
 }
 
-pub fn systemtime_to_unix_timestamp(systemtime: Result<SystemTime, io::Error>) -> Option<i64> {
+/// Returns the bytes of an `OsStr`, which are the raw bytes of the file name on unix.
+pub fn os_str_bytes(os_str: &OsStr) -> Cow<'_, [u8]> {
+    cfg_if::cfg_if! {
+        if #[cfg(unix)] {
+            use std::os::unix::ffi::OsStrExt;
+
+            Cow::Borrowed(os_str.as_bytes())
+        } else {
+            match os_str.to_string_lossy() {
+                Cow::Borrowed(str) => Cow::Borrowed(str.as_bytes()),
+                Cow::Owned(string) => Cow::Owned(string.into_bytes()),
+            }
+        }
+    }
+}
+
+/// A unix timestamp with nanosecond precision.
+///
+/// Like `struct timespec`, `nsecs` is always added to `secs`, thus timestamps
+/// before the unix epoch are also ordered correctly.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct UnixTimestamp {
+    pub secs: i64,
+    pub nsecs: u32,
+}
+
+impl UnixTimestamp {
+    pub fn new(secs: i64, nsecs: u32) -> Self {
+        Self { secs, nsecs }
+    }
+}
+
+impl From<i64> for UnixTimestamp {
+    fn from(secs: i64) -> Self {
+        Self::new(secs, 0)
+    }
+}
+
+pub fn systemtime_to_unix_timestamp(
+    systemtime: Result<SystemTime, io::Error>,
+) -> Option<UnixTimestamp> {
     systemtime
         .map(
             |sys_time| match sys_time.duration_since(SystemTime::UNIX_EPOCH) {
-                Ok(duration) => Some(duration.as_secs() as i64),
-                Err(duration_err) => Some(-(duration_err.duration().as_secs() as i64)),
+                Ok(duration) => Some(UnixTimestamp::new(
+                    duration.as_secs() as i64,
+                    duration.subsec_nanos(),
+                )),
+                Err(duration_err) => {
+                    let duration = duration_err.duration();
+                    let secs = -(duration.as_secs() as i64);
+
+                    match duration.subsec_nanos() {
+                        0 => Some(UnixTimestamp::new(secs, 0)),
+                        nsecs => Some(UnixTimestamp::new(secs - 1, 1_000_000_000 - nsecs)),
+                    }
+                }
             },
         )
         .unwrap_or(None)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::Duration;
+
+    #[test]
+    fn test_systemtime_to_unix_timestamp() {
+        let systemtime = SystemTime::UNIX_EPOCH + Duration::new(1_700_000_000, 123_456_789);
+
+        assert_eq!(
+            systemtime_to_unix_timestamp(Ok(systemtime)),
+            Some(UnixTimestamp::new(1_700_000_000, 123_456_789))
+        );
+    }
+
+    #[test]
+    fn test_systemtime_to_unix_timestamp_before_epoch() {
+        let systemtime = SystemTime::UNIX_EPOCH - Duration::new(10, 250_000_000);
+
+        assert_eq!(
+            systemtime_to_unix_timestamp(Ok(systemtime)),
+            Some(UnixTimestamp::new(-11, 750_000_000))
+        );
+        assert!(UnixTimestamp::new(-11, 750_000_000) < UnixTimestamp::new(-10, 0));
+    }
+}
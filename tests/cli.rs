@@ -0,0 +1,375 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::thread;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// Runs nls in `dir` with `args` and returns what it printed to stdout.
+fn nls(dir: &Path, args: &[&str]) -> Vec<u8> {
+    nls_with_env(dir, args, &[])
+}
+
+/// Runs nls like [`nls`] with the environment variables of `envs` set.
+fn nls_with_env(dir: &Path, args: &[&str], envs: &[(&str, &str)]) -> Vec<u8> {
+    let output = Command::new(env!("CARGO_BIN_EXE_nls"))
+        .args(args)
+        .current_dir(dir)
+        .env("LC_ALL", "C")
+        .env_remove("COLUMNS")
+        .env_remove("QUOTING_STYLE")
+        .env_remove("TIME_STYLE")
+        .envs(envs.iter().copied())
+        .output()
+        .expect("failed to run nls");
+    assert!(output.status.success(), "nls {:?} failed", args);
+
+    output.stdout
+}
+
+fn nls_string(dir: &Path, args: &[&str]) -> String {
+    String::from_utf8(nls(dir, args)).unwrap()
+}
+
+/// Creates the empty files and the directories (paths ending with '/') of `paths`
+/// in a new temporary directory.
+fn temp_tree(paths: &[&str]) -> tempfile::TempDir {
+    let tmpdir = tempfile::tempdir().unwrap();
+    for path in paths {
+        let full_path = tmpdir.path().join(path);
+        if path.ends_with('/') {
+            fs::create_dir_all(&full_path).unwrap();
+        } else {
+            fs::create_dir_all(full_path.parent().unwrap()).unwrap();
+            fs::write(&full_path, "").unwrap();
+        }
+    }
+
+    tmpdir
+}
+
+/// Returns the directory heading lines such as `./a:` in the order they are printed.
+fn dir_headings(stdout: &str) -> Vec<&str> {
+    stdout.lines().filter(|line| line.ends_with(':')).collect()
+}
+
+#[test]
+fn test_recursive_walk_order() {
+    let tmpdir = temp_tree(&["a/a1/", "a/a2/", "b/b1/"]);
+
+    let stdout = nls_string(tmpdir.path(), &["-R"]);
+    assert_eq!(
+        dir_headings(&stdout),
+        ["./a:", "./a/a1:", "./a/a2:", "./b:", "./b/b1:"]
+    );
+    let stdout = nls_string(tmpdir.path(), &["-R", "--walk=depth-first", "-r"]);
+    assert_eq!(
+        dir_headings(&stdout),
+        ["./b:", "./b/b1:", "./a:", "./a/a2:", "./a/a1:"]
+    );
+
+    let stdout = nls_string(tmpdir.path(), &["-R", "--walk=breadth-first"]);
+    assert_eq!(
+        dir_headings(&stdout),
+        ["./a:", "./b:", "./a/a1:", "./a/a2:", "./b/b1:"]
+    );
+    let stdout = nls_string(tmpdir.path(), &["-R", "--walk=breadth-first", "-r"]);
+    assert_eq!(
+        dir_headings(&stdout),
+        ["./b:", "./a:", "./b/b1:", "./a/a2:", "./a/a1:"]
+    );
+}
+
+#[test]
+fn test_sort_args() {
+    let tmpdir = temp_tree(&["d1/", "d2/"]);
+    fs::write(tmpdir.path().join("a_small"), vec![0; 10]).unwrap();
+    fs::write(tmpdir.path().join("b_big"), vec![0; 1000]).unwrap();
+    fs::write(tmpdir.path().join("c_mid"), vec![0; 100]).unwrap();
+
+    // Command-line arguments are sorted with the active sort key
+    let args = ["-1", "c_mid", "d2", "b_big", "d1", "a_small"];
+    let stdout = nls_string(tmpdir.path(), &args);
+    assert_eq!(stdout, "a_small\nb_big\nc_mid\n\nd1:\n\nd2:\n");
+    let stdout = nls_string(tmpdir.path(), &[&args[..], &["-r"]].concat());
+    assert_eq!(stdout, "c_mid\nb_big\na_small\n\nd2:\n\nd1:\n");
+    let stdout = nls_string(tmpdir.path(), &[&args[..], &["-S"]].concat());
+    assert_eq!(stdout, "b_big\nc_mid\na_small\n\nd1:\n\nd2:\n");
+    let stdout = nls_string(tmpdir.path(), &[&args[..], &["-S", "-r"]].concat());
+    assert_eq!(stdout, "a_small\nc_mid\nb_big\n\nd2:\n\nd1:\n");
+
+    // '--no-sort-args' keeps the order they are given in
+    let stdout = nls_string(
+        tmpdir.path(),
+        &[&args[..], &["--no-sort-args", "-S"]].concat(),
+    );
+    assert_eq!(stdout, "c_mid\nb_big\na_small\n\nd2:\n\nd1:\n");
+}
+
+#[test]
+fn test_commas_format() {
+    let (a, b, c) = ("a".repeat(30), "b".repeat(30), "c".repeat(85));
+    let tmpdir = temp_tree(&[&a, &b, &c, "d"]);
+
+    // A line is wrapped before a name that would reach 80 columns,
+    // and a name longer than that is put on a line of its own
+    let stdout = nls_string(tmpdir.path(), &["-m"]);
+    assert_eq!(stdout, format!("{}, {},\n{},\nd\n", a, b, c));
+}
+
+#[test]
+fn test_commas_format_width() {
+    let tmpdir = temp_tree(&["aaa", "bbb"]);
+
+    let stdout = nls_string(tmpdir.path(), &["-m", "-w", "9"]);
+    assert_eq!(stdout, "aaa, bbb\n");
+    let stdout = nls_string(tmpdir.path(), &["-m", "-w", "8"]);
+    assert_eq!(stdout, "aaa,\nbbb\n");
+}
+
+#[test]
+fn test_zero_format() {
+    let tmpdir = temp_tree(&["a b", "new\nline", "dir/x"]);
+
+    assert_eq!(nls(tmpdir.path(), &["--zero"]), b"a b\0dir\0new\nline\0");
+    assert_eq!(
+        nls(tmpdir.path(), &["--zero", "-R"]),
+        b"a b\0dir\0new\nline\0\0./dir:\0x\0"
+    );
+    assert_eq!(
+        nls(tmpdir.path(), &["--zero", "a b", "dir"]),
+        b"a b\0\0dir:\0x\0"
+    );
+}
+
+#[test]
+fn test_grid_width() {
+    let tmpdir = temp_tree(&["aaaaaa", "bb", "ccccccccc", "d"]);
+
+    let stdout = nls_string(tmpdir.path(), &["-C", "-w", "22"]);
+    assert_eq!(stdout, "aaaaaa  ccccccccc\nbb      d\n");
+    let stdout = nls_string(tmpdir.path(), &["-x", "-w", "22"]);
+    assert_eq!(stdout, "aaaaaa     bb\nccccccccc  d\n");
+
+    // A width of 0 is unlimited
+    let stdout = nls_string(tmpdir.path(), &["-C", "-w", "0"]);
+    assert_eq!(stdout, "aaaaaa  bb  ccccccccc  d\n");
+
+    // Names wider than the width with a separator are listed one per line
+    let stdout = nls_string(tmpdir.path(), &["-C", "-w", "10"]);
+    assert_eq!(stdout, "aaaaaa\nbb\nccccccccc\nd\n");
+}
+
+#[test]
+fn test_grid_columns_env() {
+    let tmpdir = temp_tree(&["aaaaaa", "bb", "ccccccccc", "d"]);
+
+    let stdout = nls_with_env(tmpdir.path(), &["-C"], &[("COLUMNS", "22")]);
+    assert_eq!(stdout, b"aaaaaa  ccccccccc\nbb      d\n");
+
+    // '-w' takes precedence over COLUMNS, and an invalid COLUMNS is ignored
+    let stdout = nls_with_env(tmpdir.path(), &["-C", "-w", "80"], &[("COLUMNS", "22")]);
+    assert_eq!(stdout, b"aaaaaa  bb  ccccccccc  d\n");
+    let stdout = nls_with_env(tmpdir.path(), &["-C"], &[("COLUMNS", "x")]);
+    assert_eq!(stdout, b"aaaaaa  bb  ccccccccc  d\n");
+}
+
+#[test]
+fn test_grid_tabsize() {
+    let tmpdir = temp_tree(&["aaaaaa", "bb", "ccccccccc", "d"]);
+
+    // Padding passing a tab stop is a tab, as with GNU ls
+    let stdout = nls_string(tmpdir.path(), &["-C", "-w", "22", "-T", "8"]);
+    assert_eq!(stdout, "aaaaaa\tccccccccc\nbb\td\n");
+    let stdout = nls_string(tmpdir.path(), &["-x", "-T", "8"]);
+    assert_eq!(stdout, "aaaaaa\tbb  ccccccccc  d\n");
+    let stdout = nls_string(tmpdir.path(), &["-C", "-w", "22", "-T", "0"]);
+    assert_eq!(stdout, "aaaaaa  ccccccccc\nbb      d\n");
+}
+
+/// Returns the text at each `start end` offset pair of a line such as `//DIRED// 52 56 110 117`.
+fn dired_names<'a>(stdout: &'a str, line_prefix: &str) -> Vec<&'a str> {
+    let line = stdout
+        .lines()
+        .find(|line| line.starts_with(line_prefix))
+        .unwrap();
+    let offset_vec: Vec<usize> = line[line_prefix.len()..]
+        .split_whitespace()
+        .map(|offset| offset.parse().unwrap())
+        .collect();
+
+    offset_vec
+        .chunks(2)
+        .map(|offsets| &stdout[offsets[0]..offsets[1]])
+        .collect()
+}
+
+#[test]
+fn test_dired_offsets() {
+    let tmpdir = temp_tree(&["a b", "café", "main.rs", "src/lib.rs"]);
+
+    let stdout = nls_string(tmpdir.path(), &["-l", "--dired", "-R"]);
+    assert_eq!(
+        dired_names(&stdout, "//DIRED//"),
+        ["a b", "café", "main.rs", "src", "lib.rs"]
+    );
+    assert_eq!(dired_names(&stdout, "//SUBDIRED//"), ["./src"]);
+
+    for args in [
+        &["-l", "--dired", "-i", "-s", "--header"][..],
+        &["-l", "--dired", "--columns=name,size"],
+    ] {
+        let stdout = nls_string(tmpdir.path(), args);
+        assert_eq!(
+            dired_names(&stdout, "//DIRED//"),
+            ["a b", "café", "main.rs", "src"]
+        );
+    }
+
+    let stdout = nls_string(tmpdir.path(), &["-l", "--dired", "--quoting-style=c"]);
+    assert_eq!(
+        dired_names(&stdout, "//DIRED//"),
+        ["\"a b\"", "\"café\"", "\"main.rs\"", "\"src\""]
+    );
+}
+
+#[test]
+fn test_group_headings_long() {
+    let tmpdir = temp_tree(&["a.rs", "long_file_name.txt", "dir/"]);
+    fs::write(tmpdir.path().join("big"), vec![0; 123456]).unwrap();
+
+    let args = ["-l", "--group-directories-first", "--group-headings"];
+    let stdout = nls_string(tmpdir.path(), &args);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines[1], "Directories");
+    assert!(lines[2].ends_with(" dir"));
+    assert_eq!(lines[3], "");
+    assert_eq!(lines[4], "Files");
+    assert_eq!(lines.len(), 8);
+
+    // The groups are laid out in one grid, so their columns line up
+    let name_column_vec: Vec<usize> = [2, 5, 6, 7]
+        .iter()
+        .map(|i| lines[*i].rfind(' ').unwrap())
+        .collect();
+    assert!(name_column_vec
+        .iter()
+        .all(|column| *column == name_column_vec[0]));
+
+    let stdout = nls_string(tmpdir.path(), &[&args[..], &["--dired"]].concat());
+    assert_eq!(
+        dired_names(&stdout, "//DIRED//"),
+        ["dir", "a.rs", "big", "long_file_name.txt"]
+    );
+}
+
+#[test]
+fn test_csv_output() {
+    let tmpdir = temp_tree(&["a,b", "src/main.rs"]);
+
+    // The header row is printed once, and '-l' keeps the CSV output
+    let stdout = nls_string(
+        tmpdir.path(),
+        &["--output=csv", "-l", "-R", "-s", "--columns=blocks,name"],
+    );
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines[0], "allocated_size,name,path");
+    assert!(lines[1].ends_with(",\"a,b\",\"./a,b\""));
+    assert!(lines[2].ends_with(",src,./src"));
+    assert!(lines[3].ends_with(",main.rs,./src/main.rs"));
+    assert_eq!(lines.len(), 4);
+}
+
+#[test]
+fn test_time_style_nanoseconds() {
+    let tmpdir = temp_tree(&["a"]);
+
+    let stdout = nls_string(tmpdir.path(), &["-l", "--time-style=+[%s.%N]"]);
+    let timestamp = &stdout[stdout.find('[').unwrap() + 1..stdout.find(']').unwrap()];
+    let (secs, nsecs) = timestamp.split_once('.').unwrap();
+    assert!(secs.parse::<u64>().is_ok());
+    assert_eq!(nsecs.len(), 9);
+
+    // '--full-time' keeps the CSV output
+    let stdout = nls_string(tmpdir.path(), &["--output=csv", "--full-time"]);
+    assert!(stdout.starts_with("mode,nlink,user,group,size,mtime,name,path\n"));
+}
+
+#[test]
+fn test_single_column_header() {
+    let tmpdir = temp_tree(&["a", "b"]);
+
+    assert_eq!(
+        nls_string(tmpdir.path(), &["-1", "--header"]),
+        "Name\na\nb\n"
+    );
+}
+
+#[test]
+fn test_posix_collation_with_codeset() {
+    let tmpdir = temp_tree(&["a", "Ab", ".b", "B", "_z"]);
+
+    // C.UTF-8 collates by bytes like the C locale
+    let stdout = nls_with_env(tmpdir.path(), &["-1", "-A"], &[("LC_ALL", "C.UTF-8")]);
+    assert_eq!(stdout, b".b\nAb\nB\n_z\na\n");
+}
+
+#[test]
+fn test_posix_time_style_with_codeset() {
+    let tmpdir = temp_tree(&["a"]);
+    let has_iso_date = |stdout: Vec<u8>| {
+        String::from_utf8(stdout)
+            .unwrap()
+            .split_whitespace()
+            .any(|field| field.len() == 10 && field.as_bytes()[4] == b'-')
+    };
+    let args = ["-l", "--time-style=posix-long-iso"];
+
+    // A 'posix-' style is ignored in C.UTF-8 like in the C locale
+    let stdout = nls_with_env(tmpdir.path(), &args, &[("LC_ALL", "C.UTF-8")]);
+    assert!(!has_iso_date(stdout));
+    let stdout = nls_with_env(tmpdir.path(), &args, &[("LC_ALL", "en_US.UTF-8")]);
+    assert!(has_iso_date(stdout));
+}
+
+#[test]
+fn test_nanosecond_timestamps() {
+    let mtime_secs = |path: &Path| {
+        let modified = path.metadata().unwrap().modified().unwrap();
+        modified.duration_since(UNIX_EPOCH).unwrap().as_secs()
+    };
+    // Retry until both files are written within the same second
+    let tmpdir = loop {
+        let tmpdir = temp_tree(&["a"]);
+        thread::sleep(Duration::from_millis(10));
+        fs::write(tmpdir.path().join("b"), "").unwrap();
+        if mtime_secs(&tmpdir.path().join("a")) == mtime_secs(&tmpdir.path().join("b")) {
+            break tmpdir;
+        }
+    };
+
+    assert_eq!(nls_string(tmpdir.path(), &["-1", "-t"]), "b\na\n");
+    assert_eq!(nls_string(tmpdir.path(), &["-1", "-t", "-r"]), "a\nb\n");
+
+    let stdout = nls_string(tmpdir.path(), &["--output=ndjson"]);
+    let mtime_nsec_vec: Vec<u32> = stdout
+        .lines()
+        .map(|line| {
+            let value = &line[line.find("\"mtime_nsec\":").unwrap() + 13..];
+            value[..value.find(',').unwrap()].parse().unwrap()
+        })
+        .collect();
+    assert!(mtime_nsec_vec[0] < mtime_nsec_vec[1]);
+}
+
+#[test]
+fn test_named_time_zone() {
+    if cfg!(not(unix)) || !Path::new("/usr/share/zoneinfo/Asia/Tokyo").is_file() {
+        return;
+    }
+    let tmpdir = temp_tree(&["a"]);
+
+    // A named time zone is used through TZ, overriding the one set in the environment
+    let args = ["-l", "--tz=Asia/Tokyo", "--time-style=+[%z]"];
+    let stdout = nls_with_env(tmpdir.path(), &args, &[("TZ", "America/New_York")]);
+    assert!(String::from_utf8(stdout).unwrap().contains(" [+0900] a"));
+}